@@ -0,0 +1,75 @@
+//! Derive macros for [roead](https://crates.io/crates/roead). These are
+//! re-exported by roead when its `derive` feature is enabled, so this crate
+//! does not need to be used directly.
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt, parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Derive `roead::aamp::FromParameterObject` for a struct with named fields.
+///
+/// Each field is read from the parameter with the same name as the field,
+/// unless another name is given with `#[param(name = "...")]`, and converted
+/// to the field type with `ParameterObject::get_as`.
+#[proc_macro_derive(FromParameterObject, attributes(param))]
+pub fn derive_from_parameter_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_parameter_object(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_from_parameter_object(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => {
+            match &data.fields {
+                Fields::Named(fields) => &fields.named,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "FromParameterObject can only be derived for structs with named fields",
+                    ));
+                }
+            }
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FromParameterObject can only be derived for structs",
+            ));
+        }
+    };
+    let mut inits = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("Named fields should have names");
+        let mut name = ident.unraw().to_string();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("param"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("Unsupported param attribute, expected `name`"))
+                }
+            })?;
+        }
+        inits.push(quote! { #ident: obj.get_as(#name)? });
+    }
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::roead::aamp::FromParameterObject for #ident #ty_generics #where_clause {
+            fn from_parameter_object(
+                obj: &::roead::aamp::ParameterObject,
+            ) -> ::roead::Result<Self> {
+                ::core::result::Result::Ok(Self { #(#inits,)* })
+            }
+        }
+    })
+}
@@ -0,0 +1,284 @@
+//! Diffing and merging parameter archives, for example to produce and apply
+//! mod patches.
+use super::*;
+
+/// Name of the sentinel entry which records deleted keys in a diff produced by
+/// [`ParameterIO::diff`] and honored by [`ParameterIO::merge`].
+///
+/// In a diffed list, a parameter object with this name holds the hashes of
+/// deleted child objects in its `objects` parameter and deleted child lists
+/// in its `lists` parameter, both as [`Parameter::BufferU32`]. In a diffed
+/// object, a parameter with this name holds the hashes of deleted parameters
+/// as [`Parameter::BufferU32`].
+pub const DELETED_KEYS: Name = Name::from_str("__DeletedKeys__");
+
+#[inline]
+fn deleted_keys<'a, V: 'a>(
+    base: impl Iterator<Item = &'a Name>,
+    other: &ParameterStructureMap<V>,
+) -> Vec<u32> {
+    base.filter(|key| !other.contains_key(*key))
+        .map(|key| key.hash())
+        .collect()
+}
+
+fn diff_object(base: &ParameterObject, other: &ParameterObject) -> ParameterObject {
+    let mut diff: ParameterObject = other
+        .0
+        .iter()
+        .filter(|(key, param)| base.0.get(*key) != Some(*param))
+        .map(|(key, param)| (*key, param.clone()))
+        .collect();
+    let deleted = deleted_keys(base.0.keys(), &other.0);
+    if !deleted.is_empty() {
+        diff.insert(DELETED_KEYS, Parameter::BufferU32(deleted));
+    }
+    diff
+}
+
+fn diff_list(base: &ParameterList, other: &ParameterList) -> ParameterList {
+    let mut diff = ParameterList::new();
+    for (key, obj) in other.objects.0.iter() {
+        match base.objects.0.get(key) {
+            Some(base_obj) => {
+                let obj_diff = diff_object(base_obj, obj);
+                if !obj_diff.is_empty() {
+                    diff.objects.insert(*key, obj_diff);
+                }
+            }
+            None => diff.objects.insert(*key, obj.clone()),
+        }
+    }
+    for (key, list) in other.lists.0.iter() {
+        match base.lists.0.get(key) {
+            Some(base_list) => {
+                let list_diff = diff_list(base_list, list);
+                if !list_diff.objects.is_empty() || !list_diff.lists.is_empty() {
+                    diff.lists.insert(*key, list_diff);
+                }
+            }
+            None => diff.lists.insert(*key, list.clone()),
+        }
+    }
+    let deleted_objects = deleted_keys(base.objects.0.keys(), &other.objects.0);
+    let deleted_lists = deleted_keys(base.lists.0.keys(), &other.lists.0);
+    if !deleted_objects.is_empty() || !deleted_lists.is_empty() {
+        let mut deleted = ParameterObject::new();
+        if !deleted_objects.is_empty() {
+            deleted.insert("objects", Parameter::BufferU32(deleted_objects));
+        }
+        if !deleted_lists.is_empty() {
+            deleted.insert("lists", Parameter::BufferU32(deleted_lists));
+        }
+        diff.objects.insert(DELETED_KEYS, deleted);
+    }
+    diff
+}
+
+#[inline]
+fn deleted_hashes(param: Option<&Parameter>) -> &[u32] {
+    match param {
+        Some(Parameter::BufferU32(hashes)) => hashes,
+        _ => &[],
+    }
+}
+
+fn merge_object(base: &mut ParameterObject, patch: &ParameterObject) {
+    for (key, param) in patch.0.iter() {
+        if *key != DELETED_KEYS {
+            base.0.insert(*key, param.clone());
+        }
+    }
+    for hash in deleted_hashes(patch.0.get(&DELETED_KEYS)) {
+        base.remove(*hash);
+    }
+}
+
+fn merge_list(base: &mut ParameterList, patch: &ParameterList) {
+    for (key, obj) in patch.objects.0.iter() {
+        if *key == DELETED_KEYS {
+            continue;
+        }
+        match base.objects.0.get_mut(key) {
+            Some(base_obj) => merge_object(base_obj, obj),
+            None => base.objects.insert(*key, obj.clone()),
+        }
+    }
+    for (key, list) in patch.lists.0.iter() {
+        match base.lists.0.get_mut(key) {
+            Some(base_list) => merge_list(base_list, list),
+            None => base.lists.insert(*key, list.clone()),
+        }
+    }
+    if let Some(deleted) = patch.objects.0.get(&DELETED_KEYS) {
+        for hash in deleted_hashes(deleted.get("objects")) {
+            base.objects.remove(*hash);
+        }
+        for hash in deleted_hashes(deleted.get("lists")) {
+            base.lists.remove(*hash);
+        }
+    }
+}
+
+impl ParameterIO {
+    /// Compute the difference from this parameter IO to another one,
+    /// returning a new parameter IO which contains only the lists, objects,
+    /// and parameters which were added in `other` or whose values differ.
+    /// Changed lists and objects are diffed recursively. The version and data
+    /// type are taken from `other`.
+    ///
+    /// Keys which are present here but missing from `other` are recorded
+    /// using the [`DELETED_KEYS`] sentinel entry.
+    pub fn diff(&self, other: &ParameterIO) -> ParameterIO {
+        ParameterIO {
+            version: other.version,
+            data_type: other.data_type.clone(),
+            param_root: diff_list(&self.param_root, &other.param_root),
+        }
+    }
+
+    /// Apply a patch (such as one produced by [`ParameterIO::diff`]) onto this
+    /// parameter IO. New lists, objects, and parameters are inserted and
+    /// existing parameters are overwritten, matching keys by hash. Lists and
+    /// objects present on both sides are merged recursively rather than
+    /// replaced wholesale. The version and data type are taken from the patch.
+    ///
+    /// Entries recorded in a [`DELETED_KEYS`] sentinel are removed.
+    #[doc(alias = "apply_patch")]
+    pub fn merge(&mut self, patch: &ParameterIO) {
+        self.version = patch.version;
+        self.data_type = patch.data_type.clone();
+        merge_list(&mut self.param_root, &patch.param_root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> ParameterIO {
+        ParameterIO::new()
+            .with_object(
+                "Config",
+                params!(
+                    "Speed" => Parameter::F32(1.0),
+                    "Name" => Parameter::StringRef("Vanilla".into()),
+                    "Unused" => Parameter::Bool(false),
+                ),
+            )
+            .with_object("Extra", params!("Flag" => Parameter::Bool(true)))
+            .with_list(
+                "Children",
+                ParameterList::new()
+                    .with_object("Child", params!("Value" => Parameter::I32(1)))
+                    .with_list("Same", ParameterList::new()),
+            )
+    }
+
+    fn modified() -> ParameterIO {
+        let mut modified = base();
+        modified
+            .object_mut("Config")
+            .unwrap()
+            .insert("Speed", Parameter::F32(2.0));
+        modified
+            .object_mut("Extra")
+            .unwrap()
+            .insert("Count", Parameter::U32(3));
+        let children = modified.list_mut("Children").unwrap();
+        children.set_object("New", params!("Value" => Parameter::I32(2)));
+        children
+            .list_mut("Same")
+            .unwrap()
+            .set_list("Nested", ParameterList::new());
+        modified
+            .with_version(1)
+            .with_list("NewList", ParameterList::new())
+    }
+
+    #[test]
+    fn diff() {
+        let base = base();
+        assert_eq!(base.diff(&base), ParameterIO::new());
+
+        let mut modified = base.clone();
+        let config = modified.object_mut("Config").unwrap();
+        config.insert("Speed", Parameter::F32(2.0));
+        config.remove("Unused");
+        modified.objects_mut().remove("Extra");
+        modified
+            .list_mut("Children")
+            .unwrap()
+            .set_object("New", params!("Value" => Parameter::I32(2)));
+
+        let diff = base.diff(&modified);
+        assert_eq!(diff.objects().len(), 2);
+        assert_eq!(
+            diff.object("Config").unwrap(),
+            &params!(
+                "Speed" => Parameter::F32(2.0),
+                DELETED_KEYS => Parameter::BufferU32(vec![Name::from_str("Unused").hash()]),
+            )
+        );
+        assert_eq!(
+            diff.object(DELETED_KEYS).unwrap(),
+            &params!(
+                "objects" => Parameter::BufferU32(vec![Name::from_str("Extra").hash()])
+            )
+        );
+        let children = diff.list("Children").unwrap();
+        assert!(children.lists.is_empty());
+        assert_eq!(children.objects.len(), 1);
+        assert_eq!(
+            children.object("New").unwrap(),
+            &params!("Value" => Parameter::I32(2))
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let base = base();
+        let modified = modified();
+        let mut merged = base.clone();
+        merged.merge(&base.diff(&modified));
+        assert_eq!(merged, modified);
+
+        let mut merged = base.clone();
+        merged.merge(&ParameterIO::new());
+        assert_eq!(merged, base);
+    }
+
+    #[test]
+    fn merge_fixture() {
+        let data = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+        let base = ParameterIO::from_binary(data).unwrap();
+        let mut modified = base.clone();
+        modified
+            .reencode_strings(|bytes| Ok(std::str::from_utf8(bytes)?.to_uppercase()))
+            .unwrap();
+        modified.objects_mut().remove("LinkTarget");
+        let patch = base.diff(&modified);
+        let mut patched = base.clone();
+        patched.merge(&patch);
+        assert_eq!(patched, modified);
+    }
+
+    #[test]
+    fn merge_deletions() {
+        let base = base();
+        let mut modified = modified();
+        modified
+            .object_mut("Config")
+            .unwrap()
+            .remove("Unused");
+        modified.objects_mut().remove("Extra");
+        modified
+            .list_mut("Children")
+            .unwrap()
+            .lists
+            .remove("Same");
+        let mut merged = base.clone();
+        merged.merge(&base.diff(&modified));
+        assert_eq!(merged, modified);
+    }
+}
@@ -31,20 +31,27 @@
 //! [`ParameterListMap`]) can take either a name or a hash for key-based
 //! operations, and likewise can be indexed by the same. As usual, indexing into
 //! a non-existent key will panic.
+mod merge;
 #[cfg(feature = "aamp-names")]
 mod names;
 mod parser;
+mod schema;
 #[cfg(feature = "yaml")]
 mod text;
 mod writer;
 use binrw::binrw;
 use indexmap::IndexMap;
+pub use merge::DELETED_KEYS;
 #[cfg(feature = "aamp-names")]
 pub use names::{get_default_name_table, NameTable};
 use num_traits::AsPrimitive;
+#[cfg(feature = "derive")]
+pub use roead_derive::FromParameterObject;
+pub use schema::{AampSchema, ListSchema, ObjectSchema};
 #[cfg(feature = "with-serde")]
 use serde::{Deserialize, Serialize};
 use smartstring::alias::String;
+pub use writer::AampWriteOptions;
 
 use crate::{types::*, util::u24, Error, Result};
 
@@ -100,14 +107,24 @@ fn check_hasher() {
     assert_eq!(HASHED, HASH);
 }
 
-#[derive(Debug)]
+#[cfg(test)]
+#[test]
+fn name_from_hash() {
+    const NAME: Name = Name::from_hash(0x41afa934);
+    assert_eq!(NAME, Name::from_str("The Abolition of Man"));
+    assert_eq!(NAME, Name::from(0x41afa934));
+    assert_ne!(Name::from_hash(0x41afa934), Name::from("1102031156"));
+}
+
+/// The type of a [`Parameter`], without its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[binrw::binrw]
 #[repr(u8)]
 #[brw(repr = u8)]
-enum Type {
+pub enum ParameterType {
     Bool = 0,
     F32,
-    Int,
+    I32,
     Vec2,
     Vec3,
     Vec4,
@@ -128,6 +145,35 @@ enum Type {
     StringRef,
 }
 
+impl ParameterType {
+    /// The name of the type, as used in type errors.
+    pub const fn name(self) -> &'static str {
+        match self {
+            ParameterType::Bool => "Bool",
+            ParameterType::F32 => "F32",
+            ParameterType::I32 => "I32",
+            ParameterType::Vec2 => "Vec2",
+            ParameterType::Vec3 => "Vec3",
+            ParameterType::Vec4 => "Vec4",
+            ParameterType::Color => "Color",
+            ParameterType::String32 => "String32",
+            ParameterType::String64 => "String64",
+            ParameterType::Curve1 => "Curve1",
+            ParameterType::Curve2 => "Curve2",
+            ParameterType::Curve3 => "Curve3",
+            ParameterType::Curve4 => "Curve4",
+            ParameterType::BufferInt => "BufferInt",
+            ParameterType::BufferF32 => "BufferF32",
+            ParameterType::String256 => "String256",
+            ParameterType::Quat => "Quat",
+            ParameterType::U32 => "U32",
+            ParameterType::BufferU32 => "BufferU32",
+            ParameterType::BufferBinary => "BufferBinary",
+            ParameterType::StringRef => "StringRef",
+        }
+    }
+}
+
 #[derive(Debug)]
 #[binrw]
 #[brw(little, magic = b"AAMP")]
@@ -153,7 +199,7 @@ struct ResHeader {
 struct ResParameter {
     name: Name,
     data_rel_offset: u24,
-    type_: Type,
+    type_: ParameterType,
 }
 
 #[derive(Debug)]
@@ -231,29 +277,7 @@ pub enum Parameter {
 
 impl Parameter {
     fn type_name(&self) -> String {
-        match self {
-            Parameter::Bool(_) => "Bool".into(),
-            Parameter::F32(_) => "F32".into(),
-            Parameter::I32(_) => "I32".into(),
-            Parameter::Vec2(_) => "Vec2".into(),
-            Parameter::Vec3(_) => "Vec3".into(),
-            Parameter::Vec4(_) => "Vec4".into(),
-            Parameter::Color(_) => "Color".into(),
-            Parameter::String32(_) => "String32".into(),
-            Parameter::String64(_) => "String64".into(),
-            Parameter::Curve1(_) => "Curve1".into(),
-            Parameter::Curve2(_) => "Curve2".into(),
-            Parameter::Curve3(_) => "Curve3".into(),
-            Parameter::Curve4(_) => "Curve4".into(),
-            Parameter::BufferInt(_) => "BufferInt".into(),
-            Parameter::BufferF32(_) => "BufferF32".into(),
-            Parameter::String256(_) => "String256".into(),
-            Parameter::Quat(_) => "Quat".into(),
-            Parameter::U32(_) => "U32".into(),
-            Parameter::BufferU32(_) => "BufferU32".into(),
-            Parameter::BufferBinary(_) => "BufferBinary".into(),
-            Parameter::StringRef(_) => "StringRef".into(),
-        }
+        self.get_type().name().into()
     }
 
     /// Get the inner bool value.
@@ -698,6 +722,45 @@ impl Parameter {
         }
     }
 
+    /// Get the components of a vector, color, or quaternion value as a
+    /// slice of floats, regardless of the exact type. Components are in
+    /// declaration order: `x, y, z, t` for vectors, `r, g, b, a` for colors,
+    /// and `a, b, c, d` for quaternions.
+    pub fn as_f32_slice(&self) -> Result<&[f32]> {
+        /// Types which are `repr(C)` structs made up only of `f32` fields.
+        trait F32Components {}
+        impl F32Components for Vector2f {}
+        impl F32Components for Vector3f {}
+        impl F32Components for Vector4f {}
+        impl F32Components for Color {}
+        impl F32Components for Quat {}
+
+        fn components<T: F32Components>(value: &T) -> &[f32] {
+            // SAFETY: `F32Components` is only implemented for `repr(C)`
+            // structs made up only of `f32` fields, which have the same
+            // layout as an array of `f32` and no padding.
+            unsafe {
+                std::slice::from_raw_parts(
+                    value as *const T as *const f32,
+                    std::mem::size_of::<T>() / std::mem::size_of::<f32>(),
+                )
+            }
+        }
+        match self {
+            Parameter::Vec2(v) => Ok(components(v)),
+            Parameter::Vec3(v) => Ok(components(v)),
+            Parameter::Vec4(v) => Ok(components(v)),
+            Parameter::Color(v) => Ok(components(v)),
+            Parameter::Quat(v) => Ok(components(v)),
+            _ => {
+                Err(Error::TypeError(
+                    self.type_name(),
+                    "Vec2, Vec3, Vec4, Color, or Quat",
+                ))
+            }
+        }
+    }
+
     /// Get the inner u32 value.
     pub fn as_u32(&self) -> Result<u32> {
         match self {
@@ -1201,29 +1264,29 @@ impl Eq for Parameter {}
 
 impl Parameter {
     #[inline(always)]
-    fn get_type(&self) -> Type {
-        match self {
-            Parameter::Bool(_) => Type::Bool,
-            Parameter::F32(_) => Type::F32,
-            Parameter::I32(_) => Type::Int,
-            Parameter::Vec2(_) => Type::Vec2,
-            Parameter::Vec3(_) => Type::Vec3,
-            Parameter::Vec4(_) => Type::Vec4,
-            Parameter::Color(_) => Type::Color,
-            Parameter::String32(_) => Type::String32,
-            Parameter::String64(_) => Type::String64,
-            Parameter::Curve1(_) => Type::Curve1,
-            Parameter::Curve2(_) => Type::Curve2,
-            Parameter::Curve3(_) => Type::Curve3,
-            Parameter::Curve4(_) => Type::Curve4,
-            Parameter::BufferInt(_) => Type::BufferInt,
-            Parameter::BufferF32(_) => Type::BufferF32,
-            Parameter::String256(_) => Type::String256,
-            Parameter::Quat(_) => Type::Quat,
-            Parameter::U32(_) => Type::U32,
-            Parameter::BufferU32(_) => Type::BufferU32,
-            Parameter::BufferBinary(_) => Type::BufferBinary,
-            Parameter::StringRef(_) => Type::StringRef,
+    fn get_type(&self) -> ParameterType {
+        match self {
+            Parameter::Bool(_) => ParameterType::Bool,
+            Parameter::F32(_) => ParameterType::F32,
+            Parameter::I32(_) => ParameterType::I32,
+            Parameter::Vec2(_) => ParameterType::Vec2,
+            Parameter::Vec3(_) => ParameterType::Vec3,
+            Parameter::Vec4(_) => ParameterType::Vec4,
+            Parameter::Color(_) => ParameterType::Color,
+            Parameter::String32(_) => ParameterType::String32,
+            Parameter::String64(_) => ParameterType::String64,
+            Parameter::Curve1(_) => ParameterType::Curve1,
+            Parameter::Curve2(_) => ParameterType::Curve2,
+            Parameter::Curve3(_) => ParameterType::Curve3,
+            Parameter::Curve4(_) => ParameterType::Curve4,
+            Parameter::BufferInt(_) => ParameterType::BufferInt,
+            Parameter::BufferF32(_) => ParameterType::BufferF32,
+            Parameter::String256(_) => ParameterType::String256,
+            Parameter::Quat(_) => ParameterType::Quat,
+            Parameter::U32(_) => ParameterType::U32,
+            Parameter::BufferU32(_) => ParameterType::BufferU32,
+            Parameter::BufferBinary(_) => ParameterType::BufferBinary,
+            Parameter::StringRef(_) => ParameterType::StringRef,
         }
     }
 
@@ -1340,10 +1403,17 @@ impl Name {
         self.0
     }
 
-    /// Const function to construct from a string.
+    /// Const function to construct from a string, which is hashed.
     pub const fn from_str(s: &str) -> Self {
         Name(hash_name(s))
     }
+
+    /// Const function to construct from a precomputed CRC32 hash. The hash is
+    /// used as is; to hash a string (even one which looks like a number), use
+    /// [`Name::from_str`] instead.
+    pub const fn from_hash(hash: u32) -> Self {
+        Name(hash)
+    }
 }
 
 macro_rules! impl_map_wrapper {
@@ -1385,6 +1455,20 @@ macro_rules! impl_map_wrapper {
                 self.0.get_mut(&key.into())
             }
 
+            /// Remove an entry by name or hash, returning its value if it
+            /// was present. This preserves the order of the other entries.
+            #[inline(always)]
+            pub fn remove<N: Into<Name>>(&mut self, key: N) -> Option<$valtype> {
+                self.0.shift_remove(&key.into())
+            }
+
+            /// Returns `true` if the map contains an entry with the given
+            /// name or hash.
+            #[inline(always)]
+            pub fn contains_key<N: Into<Name>>(&self, key: N) -> bool {
+                self.0.contains_key(&key.into())
+            }
+
             /// Get a full entry by name or hash.
             #[inline(always)]
             pub fn entry<N: Into<Name>>(&mut self, key: N) -> indexmap::map::Entry<Name, $valtype> {
@@ -1485,6 +1569,42 @@ impl ParameterObject {
         self.0.extend(iter.into_iter().map(|(k, v)| (k.into(), v)));
         self
     }
+
+    /// Get a parameter by name or hash and convert it to another type, such
+    /// as `f32` or [`Vector3f`]. Returns an error if the parameter is missing
+    /// or cannot be converted.
+    pub fn get_as<T: TryFrom<Parameter, Error = Parameter>, N: Into<Name>>(
+        &self,
+        key: N,
+    ) -> Result<T> {
+        let key = key.into();
+        self.0
+            .get(&key)
+            .ok_or_else(|| Error::InvalidDataD(format!("Missing parameter {key}")))?
+            .clone()
+            .try_into()
+            .map_err(|param: Parameter| {
+                Error::TypeError(param.type_name(), std::any::type_name::<T>())
+            })
+    }
+}
+
+/// Trait for types which can be read from a [`ParameterObject`], such as
+/// structs for known parameter schemas. With the `derive` feature, this can be
+/// derived for structs with named fields, reading each field from the
+/// parameter of the same name with [`ParameterObject::get_as`]:
+///
+/// ```ignore
+/// #[derive(FromParameterObject)]
+/// struct Config {
+///     speed: f32,
+///     #[param(name = "IsEnabled")]
+///     enabled: bool,
+/// }
+/// ```
+pub trait FromParameterObject: Sized {
+    /// Read a value from a parameter object.
+    fn from_parameter_object(obj: &ParameterObject) -> Result<Self>;
 }
 
 /// Newtype map of parameter objects.
@@ -1534,6 +1654,75 @@ pub trait ParameterListing {
     fn set_object<N: Into<Name>>(&mut self, name: N, object: ParameterObject) {
         self.objects_mut().insert(name.into(), object);
     }
+    /// Get a nested parameter by a `/`-separated path, such as
+    /// `"AI/Actions/Idx/Param"`. The last two segments are the object and
+    /// parameter names, and any preceding segments are list names. Segments
+    /// which parse as a `u32` are treated as raw hashes.
+    fn get_path(&self, path: &str) -> Option<&Parameter> {
+        let mut segments = path.split('/').map(path_segment_name);
+        let param = segments.next_back()?;
+        let object = segments.next_back()?;
+        match segments.next() {
+            Some(first) => {
+                let mut list = self.list(first)?;
+                for name in segments {
+                    list = list.list(name)?;
+                }
+                list.object(object)?.get(param)
+            }
+            None => self.object(object)?.get(param),
+        }
+    }
+    /// Get a mutable reference to a nested parameter by a `/`-separated path.
+    /// See [`ParameterListing::get_path`] for the path syntax.
+    fn get_path_mut(&mut self, path: &str) -> Option<&mut Parameter> {
+        let mut segments = path.split('/').map(path_segment_name);
+        let param = segments.next_back()?;
+        let object = segments.next_back()?;
+        match segments.next() {
+            Some(first) => {
+                let mut list = self.list_mut(first)?;
+                for name in segments {
+                    list = list.list_mut(name)?;
+                }
+                list.object_mut(object)?.get_mut(param)
+            }
+            None => self.object_mut(object)?.get_mut(param),
+        }
+    }
+    /// Set a nested parameter by a `/`-separated path, creating any missing
+    /// lists and the final object along the way. See
+    /// [`ParameterListing::get_path`] for the path syntax.
+    ///
+    /// Returns an error if the path does not contain at least an object and a
+    /// parameter name.
+    fn set_path(&mut self, path: &str, param: Parameter) -> Result<()> {
+        let mut segments = path.split('/').map(path_segment_name);
+        let (name, object) = match (segments.next_back(), segments.next_back()) {
+            (Some(name), Some(object)) => (name, object),
+            _ => return Err(Error::InvalidData("Parameter path must name an object")),
+        };
+        let objects = match segments.next() {
+            Some(first) => {
+                let mut list = self.lists_mut().0.entry(first).or_default();
+                for name in segments {
+                    list = list.lists.0.entry(name).or_default();
+                }
+                &mut list.objects
+            }
+            None => self.objects_mut(),
+        };
+        objects.0.entry(object).or_default().insert(name, param);
+        Ok(())
+    }
+}
+
+#[inline]
+fn path_segment_name(segment: &str) -> Name {
+    segment
+        .parse::<u32>()
+        .map(Name::from_hash)
+        .unwrap_or_else(|_| Name::from_str(segment))
 }
 
 /// [`Parameter`] list. This is essentially a dictionary of parameter objects
@@ -1610,6 +1799,18 @@ impl ParameterList {
 
 const ROOT_KEY: Name = Name::from_str("param_root");
 
+/// Numbers of lists, objects, and parameters in a [`ParameterIO`], as stored
+/// in the header of a binary parameter archive. See [`ParameterIO::counts`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterCounts {
+    /// Number of parameter lists, including the root list.
+    pub lists:   u32,
+    /// Number of parameter objects.
+    pub objects: u32,
+    /// Number of parameters.
+    pub params:  u32,
+}
+
 /// [`Parameter`] IO. This is the root parameter list and the only structure
 /// that can be serialized to or deserialized from a binary parameter archive.
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
@@ -1702,6 +1903,144 @@ impl ParameterIO {
         self.param_root = list;
         self
     }
+
+    /// Collect every unique string value (from `String32`, `String64`,
+    /// `String256`, and `StringRef` parameters) in the parameter IO, in the
+    /// order they are first found. Useful for extracting translatable text.
+    pub fn collect_strings(&self) -> Vec<&str> {
+        fn collect<'a>(
+            list: &'a ParameterList,
+            seen: &mut rustc_hash::FxHashSet<&'a str>,
+            strings: &mut Vec<&'a str>,
+        ) {
+            for param in list.objects.0.values().flat_map(|obj| obj.0.values()) {
+                if let Ok(s) = param.as_str() {
+                    if seen.insert(s) {
+                        strings.push(s);
+                    }
+                }
+            }
+            for list in list.lists.0.values() {
+                collect(list, seen, strings);
+            }
+        }
+
+        let mut seen = rustc_hash::FxHashSet::default();
+        let mut strings = Vec::new();
+        collect(&self.param_root, &mut seen, &mut strings);
+        strings
+    }
+
+    /// Count the lists (including the root list), objects, and parameters in
+    /// the parameter IO, exactly as they will be recorded in the header when
+    /// it is serialized to binary.
+    pub fn counts(&self) -> ParameterCounts {
+        fn count(list: &ParameterList, counts: &mut ParameterCounts) {
+            counts.lists += 1;
+            counts.objects += list.objects.len() as u32;
+            counts.params += list
+                .objects
+                .0
+                .values()
+                .map(|obj| obj.len() as u32)
+                .sum::<u32>();
+            for list in list.lists.0.values() {
+                count(list, counts);
+            }
+        }
+
+        let mut counts = ParameterCounts::default();
+        count(&self.param_root, &mut counts);
+        counts
+    }
+
+    /// Find every string parameter whose value equals `needle`, e.g. to find
+    /// all references to a resource. Each path consists of the names of the
+    /// ancestor lists (not including the root list), then the object name and
+    /// the parameter name.
+    pub fn find_string(&self, needle: &str) -> Vec<Vec<Name>> {
+        fn find(
+            list: &ParameterList,
+            needle: &str,
+            path: &mut Vec<Name>,
+            found: &mut Vec<Vec<Name>>,
+        ) {
+            for (obj_name, obj) in list.objects.0.iter() {
+                for (name, param) in obj.0.iter() {
+                    if param.as_str().is_ok_and(|value| value == needle) {
+                        let mut param_path = path.clone();
+                        param_path.extend([*obj_name, *name]);
+                        found.push(param_path);
+                    }
+                }
+            }
+            for (name, child) in list.lists.0.iter() {
+                path.push(*name);
+                find(child, needle, path, found);
+                path.pop();
+            }
+        }
+
+        let mut found = Vec::new();
+        find(&self.param_root, needle, &mut Vec::new(), &mut found);
+        found
+    }
+
+    /// Decode every string parameter again from its raw bytes with `decode`,
+    /// for example to fix Shift-JIS or Latin-1 strings in archives which
+    /// mislabel them as UTF-8. Fixed-size strings are truncated if the
+    /// decoded string is too long. Returns the first error from `decode`, in
+    /// which case only some strings may have been decoded.
+    pub fn reencode_strings<F: Fn(&[u8]) -> Result<std::string::String>>(
+        &mut self,
+        decode: F,
+    ) -> Result<()> {
+        fn reencode<F: Fn(&[u8]) -> Result<std::string::String>>(
+            list: &mut ParameterList,
+            decode: &F,
+        ) -> Result<()> {
+            for obj in list.objects.0.values_mut() {
+                for param in obj.0.values_mut() {
+                    match param {
+                        Parameter::String32(s) => *s = decode(s.as_bytes())?.into(),
+                        Parameter::String64(s) => **s = decode(s.as_bytes())?.into(),
+                        Parameter::String256(s) => **s = decode(s.as_bytes())?.into(),
+                        Parameter::StringRef(s) => *s = decode(s.as_bytes())?.into(),
+                        _ => (),
+                    }
+                }
+            }
+            for child in list.lists.0.values_mut() {
+                reencode(child, decode)?;
+            }
+            Ok(())
+        }
+
+        reencode(&mut self.param_root, &decode)
+    }
+
+    /// Iterate over every parameter list below the root list, depth first,
+    /// along with the names of its ancestor lists (not including the root
+    /// list) and its own name.
+    pub fn iter_lists(&self) -> impl Iterator<Item = (Vec<Name>, &Name, &ParameterList)> {
+        let mut stack: Vec<_> = self
+            .param_root
+            .lists
+            .0
+            .iter()
+            .rev()
+            .map(|(name, list)| (Vec::new(), name, list))
+            .collect();
+        std::iter::from_fn(move || {
+            let (path, name, list) = stack.pop()?;
+            stack.extend(list.lists.0.iter().rev().map(|(child_name, child)| {
+                let mut child_path = path.clone();
+                child_path.push(*name);
+                (child_path, child_name, child)
+            }));
+            Some((path, name, list))
+        })
+    }
 }
 
 /// Convenience macro to construct a [`ParameterObject`] with map literal syntax.
@@ -1826,3 +2165,261 @@ fn macros() {
     };
     dbg!(pio);
 }
+
+#[cfg(feature = "yaml")]
+#[test]
+fn collect_strings() {
+    let text = std::fs::read_to_string("test/aamp/test.yml").unwrap();
+    let pio = ParameterIO::from_text(text).unwrap();
+    let strings = pio.collect_strings();
+    for s in ["テスト", "strtest", "fkisfj 2929 jdj", "29103913"] {
+        assert!(strings.contains(&s), "{s} not collected");
+    }
+    assert_eq!(strings.iter().filter(|s| **s == "テスト").count(), 1);
+    assert_eq!(&strings[..2], &["test32", "test string"]);
+}
+
+#[test]
+fn get_path() {
+    let mut pio = ParameterIO::new()
+        .with_object("Root", params!("Value" => Parameter::I32(1)))
+        .with_list(
+            "AI",
+            ParameterList::new().with_list(
+                "Actions",
+                ParameterList::new().with_object("Idx", params!("Param" => Parameter::F32(2.0))),
+            ),
+        );
+    assert_eq!(pio.get_path("Root/Value"), Some(&Parameter::I32(1)));
+    assert_eq!(
+        pio.get_path("AI/Actions/Idx/Param"),
+        Some(&Parameter::F32(2.0))
+    );
+    let hashed = format!("AI/{}/Idx/{}", hash_name("Actions"), hash_name("Param"));
+    assert_eq!(pio.get_path(&hashed), Some(&Parameter::F32(2.0)));
+    assert_eq!(pio.get_path("AI/Missing/Idx/Param"), None);
+    assert_eq!(pio.get_path("Value"), None);
+
+    *pio.get_path_mut("AI/Actions/Idx/Param").unwrap() = Parameter::F32(3.0);
+    assert_eq!(pio.get_path(&hashed), Some(&Parameter::F32(3.0)));
+}
+
+#[test]
+fn set_path() {
+    let mut pio = ParameterIO::new();
+    pio.set_path("Root/Value", Parameter::I32(1)).unwrap();
+    pio.set_path("AI/Actions/Idx/Param", Parameter::F32(2.0))
+        .unwrap();
+    let hashed = format!("AI/{}/Idx/{}", hash_name("Actions"), hash_name("Other"));
+    pio.set_path(&hashed, Parameter::Bool(true)).unwrap();
+    assert_eq!(
+        pio,
+        ParameterIO::new()
+            .with_object("Root", params!("Value" => Parameter::I32(1)))
+            .with_list(
+                "AI",
+                ParameterList::new().with_list(
+                    "Actions",
+                    ParameterList::new().with_object(
+                        "Idx",
+                        params!("Param" => Parameter::F32(2.0), "Other" => Parameter::Bool(true))
+                    ),
+                ),
+            )
+    );
+    assert!(pio.set_path("Value", Parameter::I32(0)).is_err());
+}
+
+#[test]
+fn remove() {
+    let mut pio = ParameterIO::new()
+        .with_object(
+            "Obj",
+            params!("A" => Parameter::I32(1), "B" => Parameter::I32(2), "C" => Parameter::I32(3)),
+        )
+        .with_list("List", ParameterList::new());
+    let obj = pio.object_mut("Obj").unwrap();
+    assert!(obj.contains_key("B"));
+    assert_eq!(obj.remove("B"), Some(Parameter::I32(2)));
+    assert!(!obj.contains_key("B"));
+    assert_eq!(obj.remove("B"), None);
+    assert_eq!(obj.iter().map(|(k, _)| *k).collect::<Vec<_>>(), [
+        Name::from_str("A"),
+        Name::from_str("C")
+    ]);
+    assert!(pio.objects_mut().remove("Obj").is_some());
+    assert!(!pio.objects().contains_key("Obj"));
+    assert!(pio.lists_mut().remove(hash_name("List")).is_some());
+    assert!(pio.lists().is_empty());
+}
+
+#[test]
+fn as_f32_slice() {
+    let vec = Parameter::Vec3(Vector3f {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    });
+    assert_eq!(vec.as_f32_slice().unwrap(), &[1.0, 2.0, 3.0]);
+    let color = Parameter::Color(Color {
+        r: 0.1,
+        g: 0.2,
+        b: 0.3,
+        a: 1.0,
+    });
+    assert_eq!(color.as_f32_slice().unwrap(), &[0.1, 0.2, 0.3, 1.0]);
+    assert_eq!(
+        Parameter::Vec2(Vector2f { x: 4.0, y: 5.0 })
+            .as_f32_slice()
+            .unwrap(),
+        &[4.0, 5.0]
+    );
+    assert!(matches!(
+        Parameter::F32(1.0).as_f32_slice(),
+        Err(Error::TypeError(..))
+    ));
+}
+
+#[test]
+fn iter_lists() {
+    let data = std::fs::read("test/aamp/AIProgram/Assassin_Middle_Quest.baiprog").unwrap();
+    let pio = ParameterIO::from_binary(data).unwrap();
+    let lists = pio.iter_lists().collect::<Vec<_>>();
+    assert_eq!(lists[0].0, []);
+    assert_eq!(lists[0].1, &Name::from_str("AI"));
+    let (path, _, list) = lists
+        .iter()
+        .find(|(_, name, _)| **name == Name::from_str("AI_0"))
+        .unwrap();
+    assert_eq!(path, &[Name::from_str("AI")]);
+    assert_eq!(list, &pio.list("AI").unwrap().list("AI_0").unwrap());
+    assert_eq!(
+        lists.len(),
+        pio.lists().len()
+            + pio
+                .lists()
+                .iter()
+                .map(|(_, list)| list.lists.len())
+                .sum::<usize>()
+    );
+}
+
+#[test]
+fn find_string() {
+    let data = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+    let pio = ParameterIO::from_binary(data).unwrap();
+    let found = pio.find_string("GameRomHorse");
+    assert_eq!(found.len(), 7);
+    assert_eq!(found[0], [
+        Name::from_str("LinkTarget"),
+        Name::from_str("BoneControlUser")
+    ]);
+    assert!(pio.find_string("Missing").is_empty());
+
+    let data = std::fs::read("test/aamp/AIProgram/Assassin_Middle_Quest.baiprog").unwrap();
+    let pio = ParameterIO::from_binary(data).unwrap();
+    let found = pio.find_string("BasicStatusRoot");
+    assert!(found.contains(&vec![
+        Name::from_str("AI"),
+        Name::from_str("AI_0"),
+        Name::from_str("Def"),
+        Name::from_str("ClassName"),
+    ]));
+    for path in found {
+        let path = path
+            .iter()
+            .map(|name| name.hash().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        assert_eq!(
+            pio.get_path(&path).unwrap().as_str().unwrap(),
+            "BasicStatusRoot"
+        );
+    }
+}
+
+#[cfg(all(feature = "derive", feature = "yaml"))]
+#[test]
+fn derive_from_parameter_object() {
+    #[derive(Debug, PartialEq, FromParameterObject)]
+    struct TestContent {
+        #[param(name = "Bool_0")]
+        enabled: bool,
+        #[param(name = "F32_1")]
+        speed: f32,
+        #[param(name = "Vec3")]
+        position: Vector3f,
+        #[param(name = "U32")]
+        max: u32,
+        #[param(name = "StringRef_1")]
+        name: String,
+        #[param(name = "BufferInt")]
+        values: Vec<i32>,
+    }
+
+    let text = std::fs::read_to_string("test/aamp/test.yml").unwrap();
+    let pio = ParameterIO::from_text(text).unwrap();
+    let obj = pio.object("TestContent").unwrap();
+    assert_eq!(
+        TestContent::from_parameter_object(obj).unwrap(),
+        TestContent {
+            enabled: true,
+            speed: 500.12,
+            position: Vector3f {
+                x: 1.2,
+                y: 5.1,
+                z: 1.9,
+            },
+            max: 0xffffffff,
+            name: "strtest".into(),
+            values: vec![1, 2, 3, 4, 5],
+        }
+    );
+
+    #[derive(Debug, FromParameterObject)]
+    #[allow(dead_code)]
+    struct Mismatched {
+        #[param(name = "Bool_0")]
+        speed: f32,
+    }
+    assert!(matches!(
+        Mismatched::from_parameter_object(obj),
+        Err(Error::TypeError(..))
+    ));
+
+    #[derive(Debug, FromParameterObject)]
+    #[allow(dead_code)]
+    struct Missing {
+        missing: f32,
+    }
+    assert!(Missing::from_parameter_object(obj).is_err());
+}
+
+#[test]
+fn reencode_strings() {
+    let data = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+    let pio = ParameterIO::from_binary(data).unwrap();
+
+    let mut reencoded = pio.clone();
+    reencoded
+        .reencode_strings(|bytes| Ok(std::str::from_utf8(bytes)?.to_owned()))
+        .unwrap();
+    assert_eq!(reencoded, pio);
+
+    reencoded
+        .reencode_strings(|bytes| Ok(std::str::from_utf8(bytes)?.to_uppercase()))
+        .unwrap();
+    assert_ne!(reencoded, pio);
+    assert!(reencoded.find_string("GameRomHorse").is_empty());
+    assert_eq!(
+        reencoded.find_string("GAMEROMHORSE"),
+        pio.find_string("GameRomHorse")
+    );
+
+    let mut failed = pio.clone();
+    assert!(
+        failed
+            .reencode_strings(|_| Err(Error::InvalidData("Cannot decode string")))
+            .is_err()
+    );
+}
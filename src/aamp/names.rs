@@ -175,6 +175,36 @@ impl<'a> NameTable<'a> {
         }
     }
 
+    /// Create a name table from a list of names, one per line, optionally
+    /// including default BOTW strings. Blank lines are skipped. Useful for
+    /// loading names for games other than BOTW.
+    pub fn from_reader<R: std::io::BufRead>(
+        reader: R,
+        botw_strings: bool,
+    ) -> Result<NameTable<'a>> {
+        let table = Self::new(botw_strings);
+        for line in reader.lines() {
+            let line = line?;
+            let name = line.trim_end_matches('\r');
+            if !name.trim().is_empty() {
+                table.add_name(name.to_owned());
+            }
+        }
+        Ok(table)
+    }
+
+    /// Load a name table from a text file with one name per line, optionally
+    /// including default BOTW strings. See [`NameTable::from_reader`].
+    pub fn load_file<P: AsRef<std::path::Path>>(
+        path: P,
+        botw_strings: bool,
+    ) -> Result<NameTable<'a>> {
+        Self::from_reader(
+            std::io::BufReader::new(std::fs::File::open(path)?),
+            botw_strings,
+        )
+    }
+
     /// Add a known string to the name table.
     pub fn add_name(&self, name: impl Into<Cow<'a, str>>) {
         let name = name.into();
@@ -283,3 +313,112 @@ static DEFAULT_NAME_TABLE: LazyLock<Arc<NameTable<'static>>> =
 pub fn get_default_name_table() -> &'static LazyLock<Arc<NameTable<'static>>> {
     &DEFAULT_NAME_TABLE
 }
+
+fn write_name(
+    tree: &mut std::string::String,
+    table: &NameTable,
+    name: Name,
+    index: usize,
+    parent: u32,
+) {
+    match table.get_name(name.hash(), index, parent) {
+        Some(name) => tree.push_str(name),
+        None => {
+            let _ = write!(tree, "{}", name.hash());
+        }
+    }
+}
+
+fn write_list_tree(
+    tree: &mut std::string::String,
+    table: &NameTable,
+    list: &ParameterList,
+    list_hash: u32,
+    depth: usize,
+) {
+    for (i, (name, object)) in list.objects.0.iter().enumerate() {
+        tree.push_str(&"  ".repeat(depth));
+        write_name(tree, table, *name, i, list_hash);
+        tree.push_str(": object\n");
+        for (j, (param_name, param)) in object.0.iter().enumerate() {
+            tree.push_str(&"  ".repeat(depth + 1));
+            write_name(tree, table, *param_name, j, name.hash());
+            let _ = writeln!(tree, ": {}", param.type_name());
+        }
+    }
+    for (i, (name, child)) in list.lists.0.iter().enumerate() {
+        tree.push_str(&"  ".repeat(depth));
+        write_name(tree, table, *name, i, list_hash);
+        tree.push_str(": list\n");
+        write_list_tree(tree, table, child, name.hash(), depth + 1);
+    }
+}
+
+impl ParameterIO {
+    /// Build an indented outline of the structure of the parameter IO, with
+    /// names resolved from the given table where possible. Each object and
+    /// list is listed under its parent, and each parameter under its object
+    /// along with its type:
+    ///
+    /// ```text
+    /// param_root: list
+    ///   TestContent: object
+    ///     Bool_0: Bool
+    ///     F32_0: F32
+    /// ```
+    ///
+    /// Names which cannot be resolved are shown as their hash.
+    pub fn tree_string(&self, table: &NameTable) -> std::string::String {
+        let mut tree = std::string::String::from("param_root: list\n");
+        write_list_tree(&mut tree, table, &self.param_root, ROOT_KEY.hash(), 1);
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn tree_string() {
+        let text = std::fs::read_to_string("test/aamp/test.yml").unwrap();
+        let pio = ParameterIO::from_text(text).unwrap();
+        let table = NameTable::new(false);
+        for name in ["TestContent", "Bool_0", "Bool_1", "BufferBinary"] {
+            table.add_name(name);
+        }
+        let tree = pio.tree_string(&table);
+        let mut lines = tree.lines();
+        assert_eq!(lines.next(), Some("param_root: list"));
+        assert_eq!(lines.next(), Some("  TestContent: object"));
+        assert_eq!(lines.next(), Some("    Bool_0: Bool"));
+        assert_eq!(lines.next(), Some("    Bool_1: Bool"));
+        // Names missing from the table are shown as hashes
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("    {}: F32", hash_name("F32_0"))
+        );
+        assert!(tree.contains("    BufferBinary: BufferBinary\n"));
+    }
+
+    #[test]
+    fn from_reader() {
+        let text = "PlayerParam\r\n\r\nCameraParam\n  \nPlayerParam\n";
+        let table = NameTable::from_reader(std::io::Cursor::new(text), false).unwrap();
+        assert_eq!(table.names.len(), 2);
+        for name in ["PlayerParam", "CameraParam"] {
+            assert_eq!(table.get_name(hash_name(name), 0, 0).unwrap(), name);
+        }
+
+        let path = std::env::temp_dir().join("roead_name_table.txt");
+        std::fs::write(&path, text).unwrap();
+        let table = NameTable::load_file(&path, true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            table.get_name(hash_name("CameraParam"), 0, 0).unwrap(),
+            "CameraParam"
+        );
+        assert_eq!(table.get_name(hash_name("AI"), 0, 0).unwrap(), "AI");
+    }
+}
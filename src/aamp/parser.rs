@@ -11,12 +11,23 @@ impl ParameterIO {
         Parser::new(reader)?.parse()
     }
 
+    /// Read a parameter archive from a reader which need not support seeking,
+    /// such as a network stream, by first buffering all of its contents.
+    ///
+    /// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+    /// this function automatically decompresses the data when necessary.
+    pub fn read_all<R: Read>(mut reader: R) -> Result<ParameterIO> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::from_binary(data)
+    }
+
     /// Load a parameter archive from binary data.
     ///
-    /// **Note**: If and only if the `yaz0` feature is enabled, this function
-    /// automatically decompresses the data when necessary.
+    /// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+    /// this function automatically decompresses the data when necessary.
     pub fn from_binary(data: impl AsRef<[u8]>) -> Result<ParameterIO> {
-        #[cfg(feature = "yaz0")]
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
         {
             if data.as_ref().starts_with(b"Yaz0") {
                 return Parser::new(std::io::Cursor::new(crate::yaz0::decompress(
@@ -27,6 +38,30 @@ impl ParameterIO {
         }
         Parser::new(std::io::Cursor::new(data.as_ref()))?.parse()
     }
+
+    /// Load a parameter archive from binary data like
+    /// [`ParameterIO::from_binary`], but first check that the file size and
+    /// section sizes declared in the header match the actual layout of the
+    /// data. This catches truncated or tampered files which would otherwise
+    /// parse successfully.
+    ///
+    /// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+    /// this function automatically decompresses the data when necessary.
+    pub fn from_binary_validated(data: impl AsRef<[u8]>) -> Result<ParameterIO> {
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        {
+            if data.as_ref().starts_with(b"Yaz0") {
+                let mut parser = Parser::new(std::io::Cursor::new(crate::yaz0::decompress(
+                    data.as_ref(),
+                )?))?;
+                parser.validate_sections()?;
+                return parser.parse();
+            }
+        }
+        let mut parser = Parser::new(std::io::Cursor::new(data.as_ref()))?;
+        parser.validate_sections()?;
+        parser.parse()
+    }
 }
 
 struct Parser<R: Read + Seek> {
@@ -63,6 +98,32 @@ impl<R: Read + Seek> Parser<R> {
         })
     }
 
+    fn validate_sections(&mut self) -> Result<()> {
+        let len = SeekShim::stream_len(&mut self.reader)?;
+        if self.header.file_size as u64 != len {
+            return Err(Error::InvalidDataD(format!(
+                "Parameter archive size mismatch: header declares {:#x} bytes, found {:#x}",
+                self.header.file_size, len
+            )));
+        }
+        let data_section_begin = 0x30
+            + self.header.pio_offset as u64
+            + 0xC * self.header.list_count as u64
+            + 0x8 * self.header.object_count as u64
+            + 0x8 * self.header.param_count as u64;
+        let declared = self.header.data_section_size as u64
+            + self.header.string_section_size as u64
+            + self.header.unknown_section_size as u64;
+        let actual = len.saturating_sub(data_section_begin);
+        if declared != actual {
+            return Err(Error::InvalidDataD(format!(
+                "Parameter archive section size mismatch: header declares {declared:#x} bytes of \
+                 data, strings, and unknown sections, found {actual:#x}"
+            )));
+        }
+        Ok(())
+    }
+
     fn parse(&mut self) -> Result<ParameterIO> {
         let (root_name, param_root) = self.parse_list(self.header.pio_offset + 0x30)?;
         if root_name != ROOT_KEY {
@@ -143,27 +204,29 @@ impl<R: Read + Seek> Parser<R> {
         let data_offset = info.data_rel_offset.as_u32() * 4 + offset;
         self.seek(data_offset)?;
         let value = match info.type_ {
-            Type::Bool => Parameter::Bool(self.read::<u32>()? != 0),
-            Type::F32 => Parameter::F32(self.read::<f32>()?),
-            Type::Int => Parameter::I32(self.read()?),
-            Type::Vec2 => Parameter::Vec2(self.read()?),
-            Type::Vec3 => Parameter::Vec3(self.read()?),
-            Type::Vec4 => Parameter::Vec4(self.read()?),
-            Type::Quat => Parameter::Quat(self.read()?),
-            Type::Color => Parameter::Color(self.read()?),
-            Type::U32 => Parameter::U32(self.read()?),
-            Type::Curve1 => Parameter::Curve1(self.read()?),
-            Type::Curve2 => Parameter::Curve2(self.read()?),
-            Type::Curve3 => Parameter::Curve3(self.read()?),
-            Type::Curve4 => Parameter::Curve4(self.read()?),
-            Type::String32 => Parameter::String32(self.read()?),
-            Type::String64 => Parameter::String64(self.read()?),
-            Type::String256 => Parameter::String256(self.read()?),
-            Type::StringRef => Parameter::StringRef(self.read_null_string()?),
-            Type::BufferInt => Parameter::BufferInt(self.read_buffer::<i32>(data_offset)?),
-            Type::BufferU32 => Parameter::BufferU32(self.read_buffer::<u32>(data_offset)?),
-            Type::BufferF32 => Parameter::BufferF32(self.read_float_buffer(offset)?),
-            Type::BufferBinary => Parameter::BufferBinary(self.read_buffer::<u8>(data_offset)?),
+            ParameterType::Bool => Parameter::Bool(self.read::<u32>()? != 0),
+            ParameterType::F32 => Parameter::F32(self.read::<f32>()?),
+            ParameterType::I32 => Parameter::I32(self.read()?),
+            ParameterType::Vec2 => Parameter::Vec2(self.read()?),
+            ParameterType::Vec3 => Parameter::Vec3(self.read()?),
+            ParameterType::Vec4 => Parameter::Vec4(self.read()?),
+            ParameterType::Quat => Parameter::Quat(self.read()?),
+            ParameterType::Color => Parameter::Color(self.read()?),
+            ParameterType::U32 => Parameter::U32(self.read()?),
+            ParameterType::Curve1 => Parameter::Curve1(self.read()?),
+            ParameterType::Curve2 => Parameter::Curve2(self.read()?),
+            ParameterType::Curve3 => Parameter::Curve3(self.read()?),
+            ParameterType::Curve4 => Parameter::Curve4(self.read()?),
+            ParameterType::String32 => Parameter::String32(self.read()?),
+            ParameterType::String64 => Parameter::String64(self.read()?),
+            ParameterType::String256 => Parameter::String256(self.read()?),
+            ParameterType::StringRef => Parameter::StringRef(self.read_null_string()?),
+            ParameterType::BufferInt => Parameter::BufferInt(self.read_buffer::<i32>(data_offset)?),
+            ParameterType::BufferU32 => Parameter::BufferU32(self.read_buffer::<u32>(data_offset)?),
+            ParameterType::BufferF32 => Parameter::BufferF32(self.read_float_buffer(offset)?),
+            ParameterType::BufferBinary => {
+                Parameter::BufferBinary(self.read_buffer::<u8>(data_offset)?)
+            }
         };
         Ok((info.name, value))
     }
@@ -214,4 +277,64 @@ mod tests {
             ParameterIO::from_binary(data).unwrap();
         }
     }
+
+    #[test]
+    fn read_curve() {
+        let data = std::fs::read("test/aamp/common.bagllmap").unwrap();
+        let pio = ParameterIO::from_binary(data).unwrap();
+        let curve = &pio.object("lut_param").unwrap()["intensity0"]
+            .as_curve1()
+            .unwrap()[0];
+        assert_eq!(curve.mode(), CurveMode::Hermit2D);
+        assert_eq!(curve.control_points().len(), 9);
+        assert_eq!(curve.control_points(), &curve.floats[..9]);
+    }
+
+    #[test]
+    fn read_all() {
+        struct Stream<'a>(&'a [u8]);
+
+        impl Read for Stream<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                // Hand out small chunks like a socket would
+                let len = buf.len().min(self.0.len()).min(0x40);
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                Ok(len)
+            }
+        }
+
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        assert_eq!(
+            ParameterIO::read_all(Stream(&data)).unwrap(),
+            ParameterIO::from_binary(&data).unwrap()
+        );
+        assert!(ParameterIO::read_all(Stream(&data[..0x20])).is_err());
+    }
+
+    #[test]
+    fn validate_sections() {
+        for file in jwalk::WalkDir::new("test/aamp")
+            .into_iter()
+            .filter_map(|f| {
+                f.ok().and_then(|f| {
+                    (f.file_type().is_file() && !f.file_name().to_str().unwrap().ends_with("yml"))
+                        .then(|| f.path())
+                })
+            })
+        {
+            let data = std::fs::read(&file).unwrap();
+            ParameterIO::from_binary_validated(data).unwrap();
+        }
+
+        let mut data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        // Inflate the declared data section size
+        let size = u32::from_le_bytes(data[0x24..0x28].try_into().unwrap());
+        data[0x24..0x28].copy_from_slice(&(size + 0x10).to_le_bytes());
+        ParameterIO::from_binary(&data).unwrap();
+        match ParameterIO::from_binary_validated(&data) {
+            Err(Error::InvalidDataD(msg)) => assert!(msg.contains("section size mismatch")),
+            other => panic!("Expected section size error, got {other:?}"),
+        }
+    }
 }
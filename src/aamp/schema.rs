@@ -0,0 +1,187 @@
+use super::*;
+
+impl From<&Parameter> for ParameterType {
+    fn from(param: &Parameter) -> Self {
+        param.get_type()
+    }
+}
+
+/// Expected parameters of a parameter object.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ObjectSchema {
+    /// Map of required parameters and their types.
+    pub parameters: ParameterStructureMap<ParameterType>,
+}
+
+impl ObjectSchema {
+    /// Create a new empty object schema.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Builder-like method to require a parameter of the given type.
+    pub fn with_parameter<N: Into<Name>>(mut self, name: N, type_: ParameterType) -> Self {
+        self.parameters.insert(name.into(), type_);
+        self
+    }
+}
+
+/// Expected objects and child lists of a parameter list.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ListSchema {
+    /// Map of required parameter objects.
+    pub objects: ParameterStructureMap<ObjectSchema>,
+    /// Map of required parameter lists.
+    pub lists:   ParameterStructureMap<ListSchema>,
+}
+
+impl ListSchema {
+    /// Create a new empty list schema.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Builder-like method to require a parameter object.
+    pub fn with_object<N: Into<Name>>(mut self, name: N, object: ObjectSchema) -> Self {
+        self.objects.insert(name.into(), object);
+        self
+    }
+
+    /// Builder-like method to require a parameter list.
+    pub fn with_list<N: Into<Name>>(mut self, name: N, list: ListSchema) -> Self {
+        self.lists.insert(name.into(), list);
+        self
+    }
+}
+
+/// Lightweight description of the structure a parameter IO is expected to
+/// have, for use with [`ParameterIO::validate_against`].
+///
+/// A schema only lists what is required: any additional lists, objects, or
+/// parameters in the parameter IO are ignored.
+/// ```
+/// # use roead::aamp::*;
+/// let schema = AampSchema::new().with_object(
+///     "TestContent",
+///     ObjectSchema::new().with_parameter("Bool_0", ParameterType::Bool),
+/// );
+/// let pio = ParameterIO::new().with_object(
+///     "TestContent",
+///     ParameterObject::new().with_parameter("Bool_0", Parameter::Bool(true)),
+/// );
+/// assert!(pio.validate_against(&schema).is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AampSchema {
+    /// Schema for the root parameter list.
+    pub param_root: ListSchema,
+}
+
+impl AampSchema {
+    /// Create a new empty schema.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Builder-like method to require a parameter object in the root list.
+    pub fn with_object<N: Into<Name>>(mut self, name: N, object: ObjectSchema) -> Self {
+        self.param_root.objects.insert(name.into(), object);
+        self
+    }
+
+    /// Builder-like method to require a parameter list in the root list.
+    pub fn with_list<N: Into<Name>>(mut self, name: N, list: ListSchema) -> Self {
+        self.param_root.lists.insert(name.into(), list);
+        self
+    }
+}
+
+fn validate_object(obj: &ParameterObject, schema: &ObjectSchema, path: &str) -> Result<()> {
+    for (name, type_) in &schema.parameters {
+        let param = obj
+            .get(*name)
+            .ok_or_else(|| Error::InvalidDataD(format!("Missing parameter {path}/{name}")))?;
+        if ParameterType::from(param) != *type_ {
+            return Err(Error::TypeError(
+                format!("{} for parameter {path}/{name}", param.type_name()).into(),
+                type_.name(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_list(list: &ParameterList, schema: &ListSchema, path: &str) -> Result<()> {
+    for (name, obj_schema) in &schema.objects {
+        let obj = list.objects.get(*name).ok_or_else(|| {
+            Error::InvalidDataD(format!("Missing parameter object {path}/{name}"))
+        })?;
+        validate_object(obj, obj_schema, &format!("{path}/{name}"))?;
+    }
+    for (name, list_schema) in &schema.lists {
+        let child = list
+            .lists
+            .get(*name)
+            .ok_or_else(|| Error::InvalidDataD(format!("Missing parameter list {path}/{name}")))?;
+        validate_list(child, list_schema, &format!("{path}/{name}"))?;
+    }
+    Ok(())
+}
+
+impl ParameterIO {
+    /// Check that the parameter IO has every list, object, and parameter
+    /// required by a schema, with the correct parameter types.
+    ///
+    /// Returns an error describing the first missing or mistyped entry: an
+    /// [`Error::TypeError`] for a parameter of the wrong type, or an
+    /// [`Error::InvalidDataD`] for a missing entry.
+    pub fn validate_against(&self, schema: &AampSchema) -> Result<()> {
+        validate_list(&self.param_root, &schema.param_root, "param_root")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let schema = AampSchema::new().with_list(
+            "Config",
+            ListSchema::new().with_object(
+                "Flags",
+                ObjectSchema::new()
+                    .with_parameter("IsEnabled", ParameterType::Bool)
+                    .with_parameter("Count", ParameterType::I32),
+            ),
+        );
+        let mut pio = ParameterIO::new().with_list(
+            "Config",
+            ParameterList::new().with_object(
+                "Flags",
+                ParameterObject::new()
+                    .with_parameter("IsEnabled", Parameter::I32(1))
+                    .with_parameter("Count", Parameter::I32(3))
+                    .with_parameter("Extra", Parameter::F32(1.0)),
+            ),
+        );
+        match pio.validate_against(&schema) {
+            Err(Error::TypeError(found, expected)) => {
+                assert!(found.starts_with("I32 for parameter param_root/"));
+                assert_eq!(expected, "Bool");
+            }
+            other => panic!("Expected type error, got {other:?}"),
+        }
+
+        let flags = pio.list_mut("Config").unwrap().object_mut("Flags").unwrap();
+        flags.insert("IsEnabled", Parameter::Bool(false));
+        pio.validate_against(&schema).unwrap();
+
+        let flags = pio.list_mut("Config").unwrap().object_mut("Flags").unwrap();
+        flags.remove("Count");
+        match pio.validate_against(&schema) {
+            Err(Error::InvalidDataD(msg)) => assert!(msg.starts_with("Missing parameter")),
+            other => panic!("Expected missing parameter error, got {other:?}"),
+        }
+    }
+}
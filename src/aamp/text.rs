@@ -6,17 +6,49 @@ use crate::{types::*, yaml::*, Error, Result};
 
 impl ParameterIO {
     /// Parse ParameterIO from YAML text.
+    ///
+    /// Keys of objects, lists, and parameters may be names or raw hashes, and
+    /// both can be mixed in the same map. An unquoted key which is a valid
+    /// `u32` (like `12345`) is taken as a raw hash. Any other key, including a
+    /// quoted number (like `"678"`), is taken as a name and hashed.
     pub fn from_text(text: impl AsRef<str>) -> Result<Self> {
         let tree = Tree::parse(text.as_ref())?;
         let root_ref = tree.root_ref()?;
-        read_parameter_io(&root_ref)
+        read_parameter_io(&root_ref, None)
+    }
+
+    /// Parse ParameterIO from YAML text, adding every named (non-hash) key
+    /// in the document to the given name table. Serializing the result again
+    /// with [`ParameterIO::to_text_with_table`] and the same table then
+    /// reproduces the original names, even if they are not BOTW strings.
+    pub fn from_text_with_table(text: impl AsRef<str>, table: &NameTable) -> Result<Self> {
+        let tree = Tree::parse(text.as_ref())?;
+        let root_ref = tree.root_ref()?;
+        read_parameter_io(&root_ref, Some(table))
     }
 
     /// Serialize the parameter IO to YAML.
     pub fn to_text(&self) -> std::string::String {
+        self.to_text_with_table(get_default_name_table())
+    }
+
+    /// Serialize the parameter IO to YAML, resolving names from the given
+    /// table instead of the default one.
+    pub fn to_text_with_table(&self, table: &NameTable) -> std::string::String {
+        self.emit_text(table, true)
+    }
+
+    /// Serialize the parameter IO to YAML, applying the given output options
+    /// (such as a byte order mark, CRLF line endings, or omitting the `!obj`
+    /// and `!list` tags).
+    pub fn to_text_with_options(&self, options: YamlOptions) -> std::string::String {
+        options.apply(self.emit_text(get_default_name_table(), options.aamp_struct_tags))
+    }
+
+    fn emit_text(&self, table: &NameTable, struct_tags: bool) -> std::string::String {
         let mut tree = Tree::default();
         tree.reserve(10000);
-        write_parameter_io(&mut tree, self)
+        write_parameter_io(&mut tree, self, table, struct_tags)
             .expect("ParameterIO should serialize to YAML without error");
         tree.emit()
             .expect("ParameterIO should serialize to YAML without error")
@@ -72,11 +104,11 @@ where
         Ok(v) => Ok(v),
         Err(_) => {
             let opts = T::Options::default();
-            Ok(T::from_lexical_with_options::<
-                { lexical::NumberFormatBuilder::hexadecimal() },
-            >(
-                val.trim_start_matches("0x").as_bytes(), &opts
-            )?)
+            T::from_lexical_with_options::<{ lexical::NumberFormatBuilder::hexadecimal() }>(
+                val.trim_start_matches("0x").as_bytes(),
+                &opts,
+            )
+            .map_err(|_| Error::InvalidDataD(format!("Could not parse number '{val}'")))
         }
     }
 }
@@ -176,28 +208,36 @@ fn parse_parameter<'a, 't>(node: &'_ NodeRef<'a, 't, '_, &'t Tree<'a>>) -> Resul
         }
     } else {
         let tag_type = recognize_tag(tag).or_else(|| get_tag_based_type(tag));
-        scalar_to_value(tag, parse_scalar(tag_type, node.val()?, node.is_quoted()?)?)?
+        scalar_to_value(
+            tag,
+            parse_scalar(tag_type, node.val()?, node.is_val_quoted()?)?,
+        )?
     };
     Ok(param)
 }
 
 #[rustfmt::skip]
 macro_rules! read_map {
-    ($node:expr, $m:expr, $fn:expr) => {
+    ($node:expr, $m:expr, $fn:expr, $table:expr) => {
         if !$node.is_map()? {
             return Err(Error::InvalidData("Expected map node"));
         }
 
+        $m.0.reserve($node.num_children()?);
         for child in $node.iter()? {
             let key = child.key()?;
-            let value = $fn(&child)?;
-            let quoted = unsafe { matches!(key.as_ptr().sub(1).read(), b'\'' | b'"') };
+            let value = ($fn)(&child)?;
+            // Unquoted integer keys are raw hashes, anything else is a name
+            let quoted = child.is_key_quoted()?;
             if let Some(hash) = (!quoted)
                 .then(|| lexical::parse::<u32, &str>(key).ok())
                 .flatten()
             {
                 $m.insert(hash, value);
             } else {
+                if let Some(table) = $table {
+                    table.add_name(key.to_owned());
+                }
                 $m.insert(hash_name(key), value);
             }
         }
@@ -206,17 +246,19 @@ macro_rules! read_map {
 
 fn read_parameter_object<'a, 't>(
     node: &'_ NodeRef<'a, 't, '_, &'t Tree<'a>>,
+    table: Option<&NameTable>,
 ) -> Result<ParameterObject> {
     if !node.is_valid() {
         return Err(Error::InvalidData("Invalid YAML node for parameter object"));
     }
     let mut param_object = ParameterObject::default();
-    read_map!(node, param_object, parse_parameter);
+    read_map!(node, param_object, parse_parameter, table);
     Ok(param_object)
 }
 
 fn read_parameter_list<'a, 't>(
     node: &'_ NodeRef<'a, 't, '_, &'t Tree<'a>>,
+    table: Option<&NameTable>,
 ) -> Result<ParameterList> {
     if !node.is_valid() {
         return Err(Error::InvalidData("Invalid YAML node for parameter list"));
@@ -224,15 +266,33 @@ fn read_parameter_list<'a, 't>(
     let mut param_list = ParameterList::default();
     let lists = node.get("lists")?;
     let objects = node.get("objects")?;
-    read_map!(&objects, param_list.objects, read_parameter_object);
-    read_map!(&lists, param_list.lists, read_parameter_list);
+    read_map!(
+        &objects,
+        param_list.objects,
+        |node| read_parameter_object(node, table),
+        table
+    );
+    read_map!(
+        &lists,
+        param_list.lists,
+        |node| read_parameter_list(node, table),
+        table
+    );
     Ok(param_list)
 }
 
-fn read_parameter_io<'a, 't>(node: &'_ NodeRef<'a, 't, '_, &'t Tree<'a>>) -> Result<ParameterIO> {
+fn read_parameter_io<'a, 't>(
+    node: &'_ NodeRef<'a, 't, '_, &'t Tree<'a>>,
+    table: Option<&NameTable>,
+) -> Result<ParameterIO> {
     if !node.is_valid() {
         return Err(Error::InvalidData("Invalid YAML node for parameter IO"));
     }
+    if !node.has_val_tag()? || node.val_tag()? != "!io" {
+        return Err(Error::InvalidDataD(
+            "Not an AAMP YAML document (missing !io root)".into(),
+        ));
+    }
     let pio = ParameterIO {
         version: {
             let ver = node.get("version")?;
@@ -244,7 +304,7 @@ fn read_parameter_io<'a, 't>(node: &'_ NodeRef<'a, 't, '_, &'t Tree<'a>>) -> Res
         },
         param_root: {
             let pr = node.get("param_root")?;
-            read_parameter_list(&pr)?
+            read_parameter_list(&pr, table)?
         },
     };
     Ok(pio)
@@ -360,12 +420,14 @@ fn write_parameter<'a, 't>(
 fn write_parameter_object<'a, 't>(
     pobj: &ParameterObject,
     parent_hash: u32,
+    table: &NameTable,
+    struct_tags: bool,
     mut node: NodeRef<'a, 't, '_, &'t mut Tree<'a>>,
 ) -> Result<()> {
     node.change_type(ryml::NodeType::Map)?;
     for (i, (key, val)) in pobj.0.iter().enumerate() {
         let mut child = node.append_child()?;
-        if let Some(name) = get_default_name_table().get_name(key.0, i, parent_hash) {
+        if let Some(name) = table.get_name(key.0, i, parent_hash) {
             if lexical::parse::<u64, _>(name.as_bytes()).is_ok() {
                 let ty = child.node_type()?;
                 child.set_type_flags(ty | ryml::NodeType::WipKeyDquo)?;
@@ -376,13 +438,17 @@ fn write_parameter_object<'a, 't>(
         }
         write_parameter(val, child)?;
     }
-    node.set_val_tag("!obj")?;
+    if struct_tags {
+        node.set_val_tag("!obj")?;
+    }
     Ok(())
 }
 
 fn write_parameter_list<'a, 't>(
     plist: &ParameterList,
     parent_hash: u32,
+    table: &NameTable,
+    struct_tags: bool,
     mut node: NodeRef<'a, 't, '_, &'t mut Tree<'a>>,
 ) -> Result<()> {
     node.change_type(ryml::NodeType::Map)?;
@@ -391,7 +457,7 @@ fn write_parameter_list<'a, 't>(
     objects.change_type(ryml::NodeType::Map)?;
     for (i, (key, val)) in plist.objects.0.iter().enumerate() {
         let mut child = objects.append_child()?;
-        if let Some(name) = get_default_name_table().get_name(key.0, i, parent_hash) {
+        if let Some(name) = table.get_name(key.0, i, parent_hash) {
             if lexical::parse::<u64, _>(name.as_bytes()).is_ok() {
                 let ty = child.node_type()?;
                 child.set_type_flags(ty | ryml::NodeType::WipKeyDquo)?;
@@ -400,14 +466,14 @@ fn write_parameter_list<'a, 't>(
         } else {
             child.set_key(&lexical::to_string(key.0))?;
         }
-        write_parameter_object(val, key.0, child)?;
+        write_parameter_object(val, key.0, table, struct_tags, child)?;
     }
     let mut lists = node.append_child()?;
     lists.set_key("lists")?;
     lists.change_type(ryml::NodeType::Map)?;
     for (i, (key, val)) in plist.lists.0.iter().enumerate() {
         let mut child = lists.append_child()?;
-        if let Some(name) = get_default_name_table().get_name(key.0, i, parent_hash) {
+        if let Some(name) = table.get_name(key.0, i, parent_hash) {
             if lexical::parse::<u64, _>(name.as_bytes()).is_ok() {
                 let ty = child.node_type()?;
                 child.set_type_flags(ty | ryml::NodeType::WipKeyDquo)?;
@@ -416,13 +482,20 @@ fn write_parameter_list<'a, 't>(
         } else {
             child.set_key(&lexical::to_string(key.0))?;
         }
-        write_parameter_list(val, key.0, child)?;
+        write_parameter_list(val, key.0, table, struct_tags, child)?;
+    }
+    if struct_tags {
+        node.set_val_tag("!list")?;
     }
-    node.set_val_tag("!list")?;
     Ok(())
 }
 
-fn write_parameter_io(tree: &mut Tree<'_>, pio: &ParameterIO) -> Result<()> {
+fn write_parameter_io(
+    tree: &mut Tree<'_>,
+    pio: &ParameterIO,
+    table: &NameTable,
+    struct_tags: bool,
+) -> Result<()> {
     let mut root = tree.root_ref_mut()?;
     root.change_type(ryml::NodeType::Map)?;
     root.set_val_tag("!io")?;
@@ -431,7 +504,7 @@ fn write_parameter_io(tree: &mut Tree<'_>, pio: &ParameterIO) -> Result<()> {
     root.get_mut("type")?.set_val(&pio.data_type)?;
     let mut param_root = root.append_child()?;
     param_root.set_key("param_root")?;
-    write_parameter_list(&pio.param_root, ROOT_KEY.0, param_root)?;
+    write_parameter_list(&pio.param_root, ROOT_KEY.0, table, struct_tags, param_root)?;
     Ok(())
 }
 
@@ -502,6 +575,44 @@ mod tests {
         "56",
     ];
 
+    #[test]
+    fn missing_io_root() {
+        let text = std::fs::read_to_string("test/byml/A-1_Dynamic.yml").unwrap();
+        match ParameterIO::from_text(text) {
+            Err(Error::InvalidDataD(msg)) => {
+                assert_eq!(msg, "Not an AAMP YAML document (missing !io root)")
+            }
+            other => panic!("Expected missing !io error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bad_number() {
+        for (value, bad) in [("!u 0xZZ", "0xZZ"), ("!vec2 [1.0, 2.x]", "2.x")] {
+            let text = format!(
+                "!io
+version: 0
+type: xml
+param_root: !list
+  objects:
+    TestContent: !obj
+      Value: {value}
+  lists: {{}}
+"
+            );
+            match ParameterIO::from_text(text) {
+                Err(Error::InvalidDataD(msg)) => {
+                    assert_eq!(msg, format!("Could not parse number '{bad}'"))
+                }
+                other => panic!("Expected number error, got {other:?}"),
+            }
+        }
+        match ParameterIO::from_text("!io\nversion: x1\ntype: xml\nparam_root: !list {}") {
+            Err(Error::InvalidDataD(msg)) => assert_eq!(msg, "Could not parse number 'x1'"),
+            other => panic!("Expected number error, got {other:?}"),
+        }
+    }
+
     #[test]
     fn text_roundtrip() {
         {
@@ -518,6 +629,86 @@ mod tests {
         assert_eq!(pio, pio2);
     }
 
+    #[test]
+    fn text_options() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let pio = ParameterIO::from_binary(data).unwrap();
+        let text = pio.to_text();
+        assert_eq!(pio.to_text_with_options(Default::default()), text);
+
+        let bom = pio.to_text_with_options(YamlOptions {
+            bom: true,
+            ..Default::default()
+        });
+        assert!(bom.as_bytes().starts_with(b"\xEF\xBB\xBF!io"));
+        assert_eq!(&bom[3..], text);
+
+        let crlf = pio.to_text_with_options(YamlOptions {
+            crlf: true,
+            ..Default::default()
+        });
+        assert!(crlf.starts_with("!io\r\n"));
+        assert_eq!(crlf.matches("\r\n").count(), text.matches('\n').count());
+        assert_eq!(crlf.replace("\r\n", "\n"), text);
+        assert_eq!(
+            ParameterIO::from_text(crlf.trim_start_matches('\u{FEFF}')).unwrap(),
+            pio
+        );
+    }
+
+    #[test]
+    fn without_struct_tags() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let pio = ParameterIO::from_binary(data).unwrap();
+        let text = pio.to_text_with_options(YamlOptions {
+            aamp_struct_tags: false,
+            ..Default::default()
+        });
+        assert!(text.starts_with("!io"));
+        assert!(!text.contains("!obj") && !text.contains("!list"));
+        assert_eq!(ParameterIO::from_text(&text).unwrap(), pio);
+    }
+
+    #[test]
+    fn mixed_keys() {
+        let text = r#"!io
+version: 0
+type: xml
+param_root: !list
+  objects:
+    Obj: !obj
+      SomeName: 1
+      12345: 2
+      "678": 3
+      '910': 4
+  lists: {}
+"#;
+        let pio = ParameterIO::from_text(text).unwrap();
+        let obj = pio.object("Obj").unwrap();
+        assert_eq!(obj.len(), 4);
+        assert_eq!(obj.get("SomeName"), Some(&Parameter::I32(1)));
+        assert_eq!(obj.get(Name::from_hash(12345)), Some(&Parameter::I32(2)));
+        assert_eq!(obj.get("678"), Some(&Parameter::I32(3)));
+        assert_eq!(obj.get(Name::from_hash(678)), None);
+        assert_eq!(obj.get("910"), Some(&Parameter::I32(4)));
+    }
+
+    #[test]
+    fn text_with_table() {
+        let text = std::fs::read_to_string("test/aamp/test.yml").unwrap();
+        let table = NameTable::new(false);
+        let pio = ParameterIO::from_text_with_table(&text, &table).unwrap();
+        assert_eq!(pio, ParameterIO::from_text(&text).unwrap());
+        let text2 = pio.to_text_with_table(&table);
+        assert!(text2.contains("TestContent: !obj"));
+        assert!(text2.contains("StringRef_3: "));
+        assert_eq!(ParameterIO::from_text(&text2).unwrap(), pio);
+
+        let text3 = pio.to_text_with_table(&NameTable::new(false));
+        assert!(!text3.contains("TestContent"));
+        assert!(text3.contains(&format!("{}: !obj", hash_name("TestContent"))));
+    }
+
     #[test]
     fn bin_to_text() {
         for file in jwalk::WalkDir::new("test/aamp")
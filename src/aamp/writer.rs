@@ -13,11 +13,53 @@ use rustc_hash::FxHashMap;
 use super::*;
 use crate::{util::align, Result};
 
+/// Options for serializing a [`ParameterIO`] to binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AampWriteOptions {
+    /// Reproduce the parameter data order of Breath of the Wild `AIProgram`
+    /// and `BoneControl` documents, which are detected by object names and
+    /// counts. Archives from other games may be reordered wrongly by these
+    /// heuristics, so they can be disabled. Enabled by default.
+    pub botw_quirks: bool,
+    /// Write identical parameter data and strings only once. This keeps
+    /// tables of everything already written, so for very large documents
+    /// disabling it lowers peak memory usage at the cost of a larger file.
+    /// Enabled by default.
+    pub dedup_data:  bool,
+}
+
+impl Default for AampWriteOptions {
+    fn default() -> Self {
+        Self {
+            botw_quirks: true,
+            dedup_data:  true,
+        }
+    }
+}
+
 impl ParameterIO {
     /// Serialize the parameter IO to binary using the given writer.
+    ///
+    /// Fails if the parameter data is too large to be addressed by the
+    /// 16- and 24-bit offsets used by the format.
     pub fn write<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        self.write_with_options(writer, Default::default())
+    }
+
+    /// Serialize the parameter IO to binary using the given writer and
+    /// options.
+    ///
+    /// Fails if the parameter data is too large to be addressed by the
+    /// 16- and 24-bit offsets used by the format.
+    pub fn write_with_options<W: Write + Seek>(
+        &self,
+        writer: W,
+        options: AampWriteOptions,
+    ) -> Result<()> {
         let mut ctx = WriteContext {
             writer,
+            botw_quirks: options.botw_quirks,
+            dedup_data: options.dedup_data,
             list_count: Default::default(),
             object_count: Default::default(),
             param_count: Default::default(),
@@ -68,10 +110,28 @@ impl ParameterIO {
     }
 
     /// Serialize the parameter IO to in-memory bytes.
+    ///
+    /// # Panics
+    /// Panics if the parameter data is too large to be addressed by the
+    /// offsets used by the format. Use [`ParameterIO::write`] to handle this
+    /// as an error instead.
     pub fn to_binary(&self) -> Vec<u8> {
         let mut buf = Vec::new();
         self.write(Cursor::new(&mut buf))
-            .expect("Parameter IO should serialize to binary without error");
+            .expect("Parameter IO should be small enough for AAMP offsets");
+        buf
+    }
+
+    /// Serialize the parameter IO to in-memory bytes using the given options.
+    ///
+    /// # Panics
+    /// Panics if the parameter data is too large to be addressed by the
+    /// offsets used by the format. Use [`ParameterIO::write_with_options`] to
+    /// handle this as an error instead.
+    pub fn to_binary_with_options(&self, options: AampWriteOptions) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_with_options(Cursor::new(&mut buf), options)
+            .expect("Parameter IO should be small enough for AAMP offsets");
         buf
     }
 }
@@ -86,6 +146,50 @@ where
     Ok(())
 }
 
+/// Compute the relative offset (in 4-byte units) from a parameter to its data,
+/// which must fit in 24 bits.
+#[inline]
+fn data_rel_offset(data_offset: u32, parent_offset: u32) -> Result<u24> {
+    let rel_offset = (data_offset - parent_offset) / 4;
+    if rel_offset > 0xFFFFFF {
+        return Err(Error::InvalidDataD(
+            "AAMP archive too large: offset exceeds 24-bit range".into(),
+        ));
+    }
+    Ok(u24(rel_offset))
+}
+
+fn write_data<W: Write + Seek>(writer: &mut W, param: &Parameter) -> Result<()> {
+    let mut tmp_writer = Cursor::new(Vec::<u8>::with_capacity(0x200));
+    match param {
+        Parameter::Bool(b) => tmp_writer.write_le(&u32::from(*b))?,
+        Parameter::F32(v) => tmp_writer.write_le(&v.to_bits())?,
+        Parameter::I32(v) => tmp_writer.write_le(&v)?,
+        Parameter::Vec2(v) => tmp_writer.write_le(&v)?,
+        Parameter::Vec3(v) => tmp_writer.write_le(&v)?,
+        Parameter::Vec4(v) => tmp_writer.write_le(&v)?,
+        Parameter::Color(v) => tmp_writer.write_le(&v)?,
+        Parameter::Curve1(v) => tmp_writer.write_le(&v)?,
+        Parameter::Curve2(v) => tmp_writer.write_le(&v)?,
+        Parameter::Curve3(v) => tmp_writer.write_le(&v)?,
+        Parameter::Curve4(v) => tmp_writer.write_le(&v)?,
+        Parameter::Quat(v) => tmp_writer.write_le(&v)?,
+        Parameter::U32(v) => tmp_writer.write_le(&v)?,
+        Parameter::BufferInt(v) => write_buffer(&mut tmp_writer, v)?,
+        Parameter::BufferU32(v) => write_buffer(&mut tmp_writer, v)?,
+        Parameter::BufferF32(v) => {
+            tmp_writer.write_le(&(v.len() as u32))?;
+            for f in v {
+                tmp_writer.write_le(f)?;
+            }
+        }
+        Parameter::BufferBinary(v) => write_buffer(&mut tmp_writer, v)?,
+        _ => unreachable!("unhandled parameter type"),
+    }
+    writer.write_all(tmp_writer.into_inner().as_slice())?;
+    Ok(())
+}
+
 #[inline]
 fn hash_param_data(param: &Parameter) -> u64 {
     let mut hasher = rustc_hash::FxHasher::default();
@@ -95,6 +199,8 @@ fn hash_param_data(param: &Parameter) -> u64 {
 
 struct WriteContext<'pio, W: Write + Seek> {
     writer: W,
+    botw_quirks: bool,
+    dedup_data: bool,
     list_count: u32,
     object_count: u32,
     param_count: u32,
@@ -131,11 +237,8 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_lists(&mut self, pio: &'pio ParameterIO) -> BinResult<()> {
-        fn write<W: Write + Seek>(
-            ctx: &mut WriteContext<W>,
-            list: &ParameterList,
-        ) -> BinResult<()> {
+    fn write_lists(&mut self, pio: &'pio ParameterIO) -> Result<()> {
+        fn write<W: Write + Seek>(ctx: &mut WriteContext<W>, list: &ParameterList) -> Result<()> {
             ctx.write_offset_for_parent(list, 0x4)?;
             for (name, list) in &list.lists.0 {
                 ctx.write_list(*name, list)?;
@@ -150,7 +253,7 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_objects(&mut self, list: &ParameterList) -> BinResult<()> {
+    fn write_objects(&mut self, list: &ParameterList) -> Result<()> {
         self.write_offset_for_parent(list, 0x8)?;
         for (name, object) in &list.objects.0 {
             self.write_object(*name, object)?;
@@ -162,7 +265,7 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_parameters(&mut self, list: &ParameterList) -> BinResult<()> {
+    fn write_parameters(&mut self, list: &ParameterList) -> Result<()> {
         for list in list.lists.0.values() {
             self.write_parameters(list)?;
         }
@@ -187,6 +290,10 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         // * Recursively collect all objects for child lists. For lists, object processing happens
         //   after recursively processing child lists; however every 2 lists one object from the
         //   parent list is processed.
+        //
+        // With `botw_quirks` enabled, a couple of special cases observed in
+        // Breath of the Wild AIProgram and BoneControl documents are applied
+        // on top of this.
         fn do_collect<'ctx, 'pio, W: Write + Seek>(
             ctx: Rc<Mutex<&mut WriteContext<'pio, W>>>,
             list: &'pio ParameterList,
@@ -214,14 +321,17 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
             // If the parameter IO is a Breath of the Wild AIProgram, then it appears that
             // even the parameter IO's objects are processed after child lists.
             // This is likely a hack, but it does match observations...
-            let is_botw_aiprog = !list.objects.is_empty()
+            let botw_quirks = ctx.lock().expect("Context should unlock").botw_quirks;
+            let is_botw_aiprog = botw_quirks
+                && !list.objects.is_empty()
                 && list.objects.0.keys().next() == Some(&Name::from_str("DemoAIActionIdx"));
 
             if process_top_objects_first && !is_botw_aiprog {
                 // Again this is probably a hack but it is required for matching BoneControl
                 // documents...
+                let limit = if botw_quirks { 7 } else { usize::MAX };
                 let mut i = 0;
-                while object.borrow().is_some() && i < 7 {
+                while object.borrow().is_some() && i < limit {
                     process_one_object();
                     i += 1;
                 }
@@ -241,7 +351,7 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         do_collect(Rc::new(Mutex::new(self)), &pio.param_root, true)
     }
 
-    fn write_data_section(&mut self) -> BinResult<()> {
+    fn write_data_section(&mut self) -> Result<()> {
         let queue = std::mem::take(&mut self.param_queue);
         for param in queue {
             self.write_parameter_data(param)?;
@@ -250,7 +360,7 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_string_section(&mut self) -> BinResult<()> {
+    fn write_string_section(&mut self) -> Result<()> {
         let queue = std::mem::take(&mut self.string_param_queue);
         for param in queue {
             self.write_string(param)?;
@@ -259,7 +369,7 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_parameter_data(&mut self, param: &Parameter) -> BinResult<()> {
+    fn write_parameter_data(&mut self, param: &Parameter) -> Result<()> {
         debug_assert!(
             !param.is_string_type(),
             "`write_parameter_data` called with string parameter"
@@ -275,59 +385,46 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         // accessible underlying buffer. Moreover, by hasing the parameter
         // first we get the chance to skip writing the data even to a temp
         // buffer if it's already been written.
-        let hash = hash_param_data(param);
-        data_offset = match self.buffer_offsets.entry(hash) {
-            Entry::Occupied(entry) => *entry.get(),
-            Entry::Vacant(entry) => {
-                let mut tmp_writer = Cursor::new(Vec::<u8>::with_capacity(0x200));
-                match param {
-                    Parameter::Bool(b) => tmp_writer.write_le(&u32::from(*b))?,
-                    Parameter::F32(v) => tmp_writer.write_le(&v.to_bits())?,
-                    Parameter::I32(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Vec2(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Vec3(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Vec4(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Color(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Curve1(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Curve2(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Curve3(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Curve4(v) => tmp_writer.write_le(&v)?,
-                    Parameter::Quat(v) => tmp_writer.write_le(&v)?,
-                    Parameter::U32(v) => tmp_writer.write_le(&v)?,
-                    Parameter::BufferInt(v) => write_buffer(&mut tmp_writer, v)?,
-                    Parameter::BufferU32(v) => write_buffer(&mut tmp_writer, v)?,
-                    Parameter::BufferF32(v) => {
-                        tmp_writer.write_le(&(v.len() as u32))?;
-                        for f in v {
-                            tmp_writer.write_le(f)?;
-                        }
-                    }
-                    Parameter::BufferBinary(v) => write_buffer(&mut tmp_writer, v)?,
-                    _ => unreachable!("unhandled parameter type"),
+        if self.dedup_data {
+            let hash = hash_param_data(param);
+            data_offset = match self.buffer_offsets.entry(hash) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    write_data(&mut self.writer, param)?;
+                    existed = false;
+                    *entry.insert(data_offset)
                 }
-                self.writer.write_all(tmp_writer.into_inner().as_slice())?;
-                existed = false;
-                *entry.insert(data_offset)
-            }
-        };
+            };
+        } else {
+            write_data(&mut self.writer, param)?;
+            existed = false;
+        }
 
-        self.write_at(parent_offset + 0x4, u24((data_offset - parent_offset) / 4))?;
+        self.write_at(
+            parent_offset + 0x4,
+            data_rel_offset(data_offset, parent_offset)?,
+        )?;
         if !existed {
             self.align()?;
         }
         Ok(())
     }
 
-    fn write_string(&mut self, param: &'pio Parameter) -> BinResult<()> {
+    fn write_string(&mut self, param: &'pio Parameter) -> Result<()> {
         let parent_offset = self.get_offset(param);
         let string_ = param.as_str().expect("Parameter should be a string");
         let pos = self.writer.stream_position()? as u32;
         let mut existed = true;
-        let offset = *self.string_offsets.entry(string_).or_insert_with(|| {
+        let offset = if self.dedup_data {
+            *self.string_offsets.entry(string_).or_insert_with(|| {
+                existed = false;
+                pos
+            })
+        } else {
             existed = false;
             pos
-        });
-        self.write_at(parent_offset + 0x4, u24((offset - parent_offset) / 4))?;
+        };
+        self.write_at(parent_offset + 0x4, data_rel_offset(offset, parent_offset)?)?;
         if !existed {
             self.writer.write_le(&string_.as_bytes())?;
             self.writer.write_le(&0u8)?;
@@ -378,10 +475,13 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         &mut self,
         parent: &T,
         offset_in_parent: u32,
-    ) -> BinResult<()> {
+    ) -> Result<()> {
         let parent_offset = self.get_offset(parent);
         let current_rel_offset = (self.writer.stream_position()? as u32 - parent_offset) / 4;
-        self.write_at(parent_offset + offset_in_parent, current_rel_offset as u16)?;
+        let current_rel_offset = u16::try_from(current_rel_offset).map_err(|_| {
+            Error::InvalidDataD("AAMP archive too large: offset exceeds 16-bit range".into())
+        })?;
+        self.write_at(parent_offset + offset_in_parent, current_rel_offset)?;
         Ok(())
     }
 }
@@ -408,4 +508,111 @@ mod tests {
             assert_eq!(pio, new_pio);
         }
     }
+
+    fn oversized_data() -> ParameterIO {
+        ParameterIO::new().with_object(
+            "TestContent",
+            ParameterObject::new()
+                .with_parameter("Data", Parameter::BufferBinary(vec![0; 0x4000000]))
+                .with_parameter("Count", Parameter::I32(1)),
+        )
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let pio = oversized_data();
+        match pio.write(Cursor::new(Vec::new())) {
+            Err(Error::InvalidDataD(msg)) => {
+                assert_eq!(msg, "AAMP archive too large: offset exceeds 24-bit range")
+            }
+            other => panic!("Expected offset overflow error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn child_offset_overflow() {
+        let list = (0..0x8000).fold(ParameterList::new(), |list, i| {
+            list.with_object(
+                format!("Object{i}"),
+                ParameterObject::new().with_parameter("Value", Parameter::I32(i)),
+            )
+        });
+        let pio = ParameterIO::new().with_list("TestContent", list);
+        match pio.write(Cursor::new(Vec::new())) {
+            Err(Error::InvalidDataD(msg)) => {
+                assert_eq!(msg, "AAMP archive too large: offset exceeds 16-bit range")
+            }
+            other => panic!("Expected offset overflow error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn without_botw_quirks() {
+        let pio = ParameterIO::new()
+            .with_object(
+                "DemoAIActionIdx",
+                ParameterObject::new().with_parameter("Demo_Idle", Parameter::I32(1)),
+            )
+            .with_object(
+                "Extra",
+                ParameterObject::new().with_parameter("Value", Parameter::F32(2.0)),
+            )
+            .with_list(
+                "AI",
+                ParameterList::new().with_object(
+                    "Def",
+                    ParameterObject::new().with_parameter("ClassName", Parameter::I32(3)),
+                ),
+            );
+        let options = AampWriteOptions {
+            botw_quirks: false,
+            ..Default::default()
+        };
+        let bytes = pio.to_binary_with_options(options);
+        assert_ne!(bytes, pio.to_binary());
+        assert_eq!(ParameterIO::from_binary(bytes).unwrap(), pio);
+    }
+
+    #[test]
+    fn without_dedup() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let pio = ParameterIO::from_binary(data).unwrap();
+        let options = AampWriteOptions {
+            dedup_data: false,
+            ..Default::default()
+        };
+        let bytes = pio.to_binary_with_options(options);
+        assert!(bytes.len() > pio.to_binary().len());
+        assert_eq!(ParameterIO::from_binary(bytes).unwrap(), pio);
+    }
+
+    #[test]
+    fn counts() {
+        for file in [
+            "test/aamp/Lizalfos.bphysics",
+            "test/aamp/AIProgram/Assassin_Middle_Quest.baiprog",
+            "test/aamp/GameRomHorse.bgparamlist",
+        ] {
+            let pio = ParameterIO::from_binary(std::fs::read(file).unwrap()).unwrap();
+            let bytes = pio.to_binary();
+            let header_count =
+                |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            assert_eq!(pio.counts(), ParameterCounts {
+                lists:   header_count(0x18),
+                objects: header_count(0x1C),
+                params:  header_count(0x20),
+            });
+        }
+        assert_eq!(ParameterIO::new().counts(), ParameterCounts {
+            lists:   1,
+            objects: 0,
+            params:  0,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Parameter IO should be small enough for AAMP offsets")]
+    fn to_binary_offset_overflow() {
+        oversized_data().to_binary();
+    }
 }
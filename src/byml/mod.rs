@@ -61,6 +61,7 @@
 #[cfg(feature = "yaml")]
 mod text;
 mod writer;
+pub use writer::BymlWriteOptions;
 use num_traits::AsPrimitive;
 use smartstring::alias::String;
 
@@ -103,6 +104,42 @@ const fn is_valid_version(version: u16) -> bool {
     version >= 1 && version < 8
 }
 
+/// Hash a name for use as a key in a BYML hash map (MurmurHash3, x86 32-bit,
+/// seed 0), as used by *Tears of the Kingdom* for named keys.
+pub const fn hash_name(name: &str) -> u32 {
+    const C1: u32 = 0xcc9e2d51;
+    const C2: u32 = 0x1b873593;
+    let bytes = name.as_bytes();
+    let mut hash = 0u32;
+    let mut i = 0;
+    while i + 4 <= bytes.len() {
+        let mut k = u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash = (hash ^ k)
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe6546b64);
+        i += 4;
+    }
+    let mut k = 0u32;
+    let mut shift = 0;
+    while i < bytes.len() {
+        k |= (bytes[i] as u32) << shift;
+        shift += 8;
+        i += 1;
+    }
+    if shift > 0 {
+        hash ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+    hash ^= bytes.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^= hash >> 16;
+    hash
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum BymlError {
     #[error("Invalid version: {0}")]
@@ -163,6 +200,17 @@ impl<'a> From<u32> for BymlIndex<'a> {
     }
 }
 
+/// A single step in the path to a node in a BYML document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BymlPathSegment {
+    /// Key in a hash node with string keys.
+    Key(String),
+    /// Key in a hash node with u32 keys.
+    Hash(u32),
+    /// Index in an array node.
+    Index(usize),
+}
+
 /// Represents a Nintendo binary YAML (BYML) document or node.
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone)]
@@ -619,6 +667,160 @@ impl Byml {
             Err(Error::TypeError(self.type_name(), "ValueHashMap"))
         }
     }
+
+    /// Apply a fallible operation to each element of an array node in order,
+    /// stopping at the first error. The error is reported along with the
+    /// index of the element which caused it.
+    pub fn try_map_array<F: FnMut(&mut Byml) -> Result<()>>(&mut self, mut f: F) -> Result<()> {
+        for (i, item) in self.as_mut_array()?.iter_mut().enumerate() {
+            f(item).map_err(|e| Error::InvalidDataD(format!("Array element {i}: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// Estimate the number of bytes allocated on the heap by the node and
+    /// everything in it: string and buffer capacities, array storage, and
+    /// hash map tables. The inline size of the node itself is not included.
+    /// This is an approximation, meant for memory accounting.
+    pub fn heap_size(&self) -> usize {
+        fn string_size(s: &String) -> usize {
+            if s.is_inline() { 0 } else { s.capacity() }
+        }
+        fn table_size<K, V>(map: &rustc_hash::FxHashMap<K, V>) -> usize {
+            // One control byte per bucket in addition to the entry itself
+            map.capacity() * (std::mem::size_of::<(K, V)>() + 1)
+        }
+        match self {
+            Byml::String(s) => string_size(s),
+            Byml::BinaryData(data) | Byml::FileData(data) => data.capacity(),
+            Byml::Array(array) => {
+                array.capacity() * std::mem::size_of::<Byml>()
+                    + array.iter().map(Byml::heap_size).sum::<usize>()
+            }
+            Byml::Map(map) => {
+                table_size(map)
+                    + map
+                        .iter()
+                        .map(|(k, v)| string_size(k) + v.heap_size())
+                        .sum::<usize>()
+            }
+            Byml::HashMap(map) => {
+                table_size(map) + map.values().map(Byml::heap_size).sum::<usize>()
+            }
+            Byml::ValueHashMap(map) => {
+                table_size(map) + map.values().map(|(v, _)| v.heap_size()).sum::<usize>()
+            }
+            _ => 0,
+        }
+    }
+
+    /// Build a hash map node from named entries, hashing each key with
+    /// [`hash_name`].
+    pub fn hash_map_from_named<K: AsRef<str>, I: IntoIterator<Item = (K, Byml)>>(iter: I) -> Byml {
+        Byml::HashMap(
+            iter.into_iter()
+                .map(|(k, v)| (hash_name(k.as_ref()), v))
+                .collect(),
+        )
+    }
+
+    /// Check whether the node is a non-empty string-keyed map whose keys all
+    /// parse as `u32`, as in files which store hash keys as decimal strings
+    /// instead of using a hash map node. Such a map can be converted with
+    /// [`Byml::numeric_keys_to_hash_map`].
+    pub fn looks_like_hash_map(&self) -> bool {
+        match self {
+            Byml::Map(map) => !map.is_empty() && map.keys().all(|k| k.parse::<u32>().is_ok()),
+            _ => false,
+        }
+    }
+
+    /// Convert a string-keyed map whose keys are all decimal `u32` values
+    /// into a hash map node using those values as the keys. Fails if the node
+    /// is not a map or if any key is not a valid `u32`.
+    pub fn numeric_keys_to_hash_map(self) -> Result<Byml> {
+        self.into_map()?
+            .into_iter()
+            .map(|(k, v)| {
+                k.parse::<u32>()
+                    .map(|hash| (hash, v))
+                    .map_err(|_| Error::InvalidDataD(format!("Map key is not a u32: {k}")))
+            })
+            .collect::<Result<_>>()
+            .map(Byml::HashMap)
+    }
+
+    /// Returns an iterator over every leaf (non-container) node in the
+    /// document, along with the full path from the root to the leaf. Empty
+    /// containers are skipped.
+    ///
+    /// Siblings in hash nodes are visited in no particular order.
+    pub fn leaves(&self) -> impl Iterator<Item = (Vec<BymlPathSegment>, &Byml)> {
+        let mut stack = vec![(Vec::new(), self)];
+        std::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                let with_segment = |segment| {
+                    let mut path: Vec<BymlPathSegment> = path.clone();
+                    path.push(segment);
+                    path
+                };
+                match node {
+                    Byml::Array(arr) => {
+                        stack.extend(
+                            arr.iter()
+                                .enumerate()
+                                .rev()
+                                .map(|(i, node)| (with_segment(BymlPathSegment::Index(i)), node)),
+                        );
+                    }
+                    Byml::Map(map) => {
+                        stack.extend(map.iter().map(|(key, node)| {
+                            (with_segment(BymlPathSegment::Key(key.clone())), node)
+                        }));
+                    }
+                    Byml::HashMap(map) => {
+                        stack.extend(map.iter().map(|(hash, node)| {
+                            (with_segment(BymlPathSegment::Hash(*hash)), node)
+                        }));
+                    }
+                    Byml::ValueHashMap(map) => {
+                        stack.extend(map.iter().map(|(hash, (node, _))| {
+                            (with_segment(BymlPathSegment::Hash(*hash)), node)
+                        }));
+                    }
+                    _ => return Some((path, node)),
+                }
+            }
+            None
+        })
+    }
+
+    /// Look up a node using an [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)
+    /// JSON pointer like `/Actors/0/name`. In keys, `~1` stands for a literal
+    /// `/` and `~0` for a literal `~`. Tokens index arrays by position and
+    /// hash nodes by their u32 keys. An empty pointer refers to the node
+    /// itself.
+    ///
+    /// Returns `None` if the pointer is malformed or the node does not exist.
+    pub fn pointer(&self, ptr: &str) -> Option<&Byml> {
+        if ptr.is_empty() {
+            return Some(self);
+        }
+        ptr.strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |node, token| {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                match node {
+                    Byml::Map(map) => map.get(token.as_str()),
+                    Byml::Array(arr) => arr.get(token.parse::<usize>().ok()?),
+                    Byml::HashMap(map) => map.get(&token.parse::<u32>().ok()?),
+                    Byml::ValueHashMap(map) => {
+                        map.get(&token.parse::<u32>().ok()?).map(|(node, _)| node)
+                    }
+                    _ => None,
+                }
+            })
+    }
 }
 
 impl From<bool> for Byml {
@@ -1042,6 +1244,28 @@ macro_rules! map {
 }
 pub use map;
 
+/// Like [`map!`], but converts each value into a [`Byml`] node using the
+/// existing [`From`] impls, so plain Rust values can be used directly.
+/// Example:
+///
+/// ```
+/// # use roead::byml::*;
+/// let bmap = bmap!(
+///     "SomeKey" => true,
+///     "AnotherKey" => 0,
+///     "Name" => "test"
+/// );
+/// assert_eq!(bmap["AnotherKey"], Byml::I32(0));
+/// ```
+#[macro_export]
+macro_rules! bmap {
+    ($($key:expr => $value:expr,)+) => { $crate::byml::bmap!($($key => $value),+) };
+    ($($key:expr => $value:expr),*) => {
+        $crate::byml::map!($($key => $crate::byml::Byml::from($value)),*)
+    };
+}
+pub use bmap;
+
 /// Convenience macro to construct a [`Byml`] array using array literal syntax.
 /// Example:
 ///
@@ -1100,6 +1324,133 @@ mod tests {
         }
     }
 
+    #[test]
+    fn hash_names() {
+        assert_eq!(hash_name(""), 0);
+        assert_eq!(hash_name("hello"), 0x248bfa47);
+        assert_eq!(
+            hash_name("The quick brown fox jumps over the lazy dog"),
+            0x2e4ff723
+        );
+    }
+
+    #[test]
+    fn hash_map_from_named() {
+        let byml = Byml::hash_map_from_named([
+            ("hello", Byml::I32(1)),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                Byml::Bool(true),
+            ),
+        ]);
+        let map = byml.as_hash_map().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&0x248bfa47], Byml::I32(1));
+        assert_eq!(map[&0x2e4ff723], Byml::Bool(true));
+        assert_eq!(map[&hash_name("hello")], Byml::I32(1));
+    }
+
+    #[test]
+    fn looks_like_hash_map() {
+        let numeric = map!("123" => Byml::I32(1), "4294967295" => Byml::Bool(true));
+        assert!(numeric.looks_like_hash_map());
+        let mixed = map!("123" => Byml::I32(1), "name" => Byml::Bool(true));
+        assert!(!mixed.looks_like_hash_map());
+        assert!(!map!().looks_like_hash_map());
+        assert!(!Byml::hash_map_from_named([("a", Byml::Null)]).looks_like_hash_map());
+
+        let map = numeric.numeric_keys_to_hash_map().unwrap();
+        assert_eq!(map.as_hash_map().unwrap()[&123], Byml::I32(1));
+        assert_eq!(map.as_hash_map().unwrap()[&u32::MAX], Byml::Bool(true));
+        assert!(mixed.numeric_keys_to_hash_map().is_err());
+    }
+
+    #[test]
+    fn leaves() {
+        let actorinfo =
+            Byml::from_binary(std::fs::read("test/byml/ActorInfo.product.byml").unwrap()).unwrap();
+        let path = vec![
+            BymlPathSegment::Key("Actors".into()),
+            BymlPathSegment::Index(0),
+            BymlPathSegment::Key("name".into()),
+        ];
+        let (_, leaf) = actorinfo
+            .leaves()
+            .find(|(leaf_path, _)| leaf_path == &path)
+            .unwrap();
+        assert_eq!(leaf, &actorinfo["Actors"][0]["name"]);
+
+        let byml = array!(Byml::I32(1), array!(), array!(Byml::Null));
+        let leaves = byml.leaves().collect::<Vec<_>>();
+        assert_eq!(leaves, vec![
+            (vec![BymlPathSegment::Index(0)], &Byml::I32(1)),
+            (
+                vec![BymlPathSegment::Index(2), BymlPathSegment::Index(0)],
+                &Byml::Null
+            ),
+        ]);
+    }
+
+    #[test]
+    fn pointer() {
+        let actorinfo =
+            Byml::from_binary(std::fs::read("test/byml/ActorInfo.product.byml").unwrap()).unwrap();
+        assert_eq!(
+            actorinfo.pointer("/Actors/0/name"),
+            Some(&actorinfo["Actors"][0]["name"])
+        );
+        assert_eq!(actorinfo.pointer(""), Some(&actorinfo));
+        assert_eq!(actorinfo.pointer("/Actors/-1/name"), None);
+        assert_eq!(actorinfo.pointer("/Missing/0"), None);
+        assert_eq!(actorinfo.pointer("Actors"), None);
+
+        let byml = map!(
+            "a/b" => map!("m~n" => Byml::I32(1)),
+            "hashes" => Byml::HashMap([(123, Byml::Bool(true))].into_iter().collect()),
+        );
+        assert_eq!(byml.pointer("/a~1b/m~0n"), Some(&Byml::I32(1)));
+        assert_eq!(byml.pointer("/hashes/123"), Some(&Byml::Bool(true)));
+        assert_eq!(byml.pointer("/a/b"), None);
+    }
+
+    #[test]
+    fn heap_size() {
+        assert_eq!(Byml::I32(1).heap_size(), 0);
+        assert_eq!(Byml::String("short".into()).heap_size(), 0);
+        let mut byml = map!("a" => Byml::I32(1), "b" => Byml::Bool(true));
+        let size = byml.heap_size();
+        assert!(size > 0);
+        let long = "Lorem ipsum dolor sit amet ".repeat(40);
+        byml.as_mut_map()
+            .unwrap()
+            .insert("c".into(), Byml::String(long.as_str().into()));
+        assert!(byml.heap_size() >= size + long.len());
+
+        let bytes = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        assert!(Byml::from_binary(&bytes).unwrap().heap_size() > bytes.len());
+    }
+
+    #[test]
+    fn try_map_array() {
+        let double = |node: &mut Byml| -> Result<()> {
+            *node.as_mut_i32()? *= 2;
+            Ok(())
+        };
+        let mut arr = array!(Byml::I32(1), Byml::I32(2), Byml::I32(3));
+        arr.try_map_array(double).unwrap();
+        assert_eq!(arr, array!(Byml::I32(2), Byml::I32(4), Byml::I32(6)));
+
+        let mut arr = array!(Byml::I32(1), Byml::Float(2.0), Byml::I32(3));
+        match arr.try_map_array(double) {
+            Err(Error::InvalidDataD(msg)) => {
+                assert_eq!(msg, "Array element 1: Found Float, expected I32")
+            }
+            other => panic!("Expected error at index 1, got {other:?}"),
+        }
+        assert_eq!(arr, array!(Byml::I32(2), Byml::Float(2.0), Byml::I32(3)));
+        assert!(Byml::Null.try_map_array(double).is_err());
+    }
+
     #[test]
     fn macro_test() {
         let map = map!(
@@ -1108,5 +1459,9 @@ mod tests {
         assert_eq!(map["test"], Byml::String("bob".into()));
         let arr = array!(Byml::String("bob".into()), Byml::Bool(true));
         assert_eq!(arr.as_array().unwrap().len(), 2);
+        let bmap = bmap!("n" => 5i32, "s" => "x", "b" => true);
+        assert_eq!(bmap["n"], Byml::I32(5));
+        assert_eq!(bmap["s"], Byml::String("x".into()));
+        assert_eq!(bmap["b"], Byml::Bool(true));
     }
 }
@@ -11,24 +11,46 @@ use crate::{
 impl Byml {
     /// Read a document from a binary reader.
     pub fn read<R: Read + Seek>(reader: R) -> Result<Byml> {
-        Parser::new(reader)?.parse()
+        Parser::new(reader, None)?.parse()
     }
 
     /// Load a document from binary data.
     ///
-    /// **Note**: If and only if the `yaz0` feature is enabled, this function
-    /// automatically decompresses the SARC when necessary.
+    /// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+    /// this function automatically decompresses the SARC when necessary.
     pub fn from_binary(data: impl AsRef<[u8]>) -> Result<Byml> {
-        #[cfg(feature = "yaz0")]
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
         {
             if data.as_ref().starts_with(b"Yaz0") {
-                return Parser::new(std::io::Cursor::new(crate::yaz0::decompress(
-                    data.as_ref(),
-                )?))?
+                return Parser::new(
+                    std::io::Cursor::new(crate::yaz0::decompress(data.as_ref())?),
+                    None,
+                )?
                 .parse();
             }
         }
-        Parser::new(std::io::Cursor::new(data.as_ref()))?.parse()
+        Parser::new(std::io::Cursor::new(data.as_ref()), None)?.parse()
+    }
+
+    /// Load a document from binary data, interpreting it with the given
+    /// endianness instead of detecting it from the magic. This is useful for
+    /// nonstandard files whose magic is missing or does not match the actual
+    /// byte order of the data.
+    ///
+    /// Unlike [`Byml::from_binary`], this never decompresses the data.
+    pub fn from_binary_with_endian(data: impl AsRef<[u8]>, endian: Endian) -> Result<Byml> {
+        Parser::new(std::io::Cursor::new(data.as_ref()), Some(endian))?.parse()
+    }
+
+    /// Produce an annotated listing of the binary layout of a document, for
+    /// studying the format: the header fields, each entry of the hash key and
+    /// string tables, and the type, entry count, and offset of each container
+    /// node. Nodes referenced from more than one place are listed once and
+    /// marked as shared afterwards.
+    ///
+    /// **Note**: This never decompresses the data.
+    pub fn dump_layout(data: &[u8]) -> Result<std::string::String> {
+        SliceParser::new(data)?.dump_layout()
     }
 }
 
@@ -152,16 +174,26 @@ struct Parser<R: Read + Seek> {
 }
 
 impl<R: Read + Seek> Parser<R> {
-    fn new(mut reader: R) -> Result<Self> {
+    fn new(mut reader: R, endian: Option<Endian>) -> Result<Self> {
         if SeekShim::stream_len(&mut reader)? < 0x10 {
             return Err(Error::InvalidData("Insufficient data for header"));
         }
-        let header = ResHeader::read_ne(&mut reader)?;
-        let endian = if &header.magic == b"BY" {
+        let header = match endian {
+            Some(endian) => {
+                let magic = <[u8; 2]>::read_ne(&mut reader)?;
+                let inner = match endian {
+                    Endian::Big => ResHeaderInner::read_be(&mut reader)?,
+                    Endian::Little => ResHeaderInner::read_le(&mut reader)?,
+                };
+                ResHeader { magic, inner }
+            }
+            None => ResHeader::read_ne(&mut reader)?,
+        };
+        let endian = endian.unwrap_or(if &header.magic == b"BY" {
             Endian::Big
         } else {
             Endian::Little
-        };
+        });
         if !is_valid_version(header.inner.version) {
             return Err(Error::InvalidData("Unsupported BYML version (1-7 only)"));
         }
@@ -318,6 +350,301 @@ impl<R: Read + Seek> Parser<R> {
     }
 }
 
+impl NodeType {
+    #[inline(always)]
+    fn from_u8(value: u8) -> Result<Self> {
+        Ok(match value {
+            0x20 => NodeType::HashMap,
+            0x21 => NodeType::ValueHashMap,
+            0xa0 => NodeType::String,
+            0xa1 => NodeType::Binary,
+            0xa2 => NodeType::File,
+            0xc0 => NodeType::Array,
+            0xc1 => NodeType::Map,
+            0xc2 => NodeType::StringTable,
+            0xd0 => NodeType::Bool,
+            0xd1 => NodeType::I32,
+            0xd2 => NodeType::Float,
+            0xd3 => NodeType::U32,
+            0xd4 => NodeType::I64,
+            0xd5 => NodeType::U64,
+            0xd6 => NodeType::Double,
+            0xff => NodeType::Null,
+            _ => {
+                return Err(Error::InvalidDataD(format!(
+                    "Invalid node type: {value:#x}"
+                )));
+            }
+        })
+    }
+}
+
+/// Maximum container nesting depth listed by [`Byml::dump_layout`].
+const MAX_LAYOUT_DEPTH: usize = 256;
+
+/// Add a relative offset computed from values in the file to a node offset,
+/// failing instead of overflowing on crafted data.
+#[inline(always)]
+fn offset_add(offset: u32, rel: u64) -> Result<u32> {
+    u32::try_from(offset as u64 + rel).map_err(|_| Error::InvalidData("BYML offset out of range"))
+}
+
+/// A reader which reads directly from a byte slice rather than through a
+/// `Read + Seek` reader, used to list the layout of a document.
+struct SliceParser<'a> {
+    data: &'a [u8],
+    endian: Endian,
+    string_table: (u32, u32),
+    hash_key_table: (u32, u32),
+    root_node_offset: u32,
+}
+
+impl<'a> SliceParser<'a> {
+    fn new(data: &'a [u8]) -> Result<Self> {
+        if data.len() < 0x10 {
+            return Err(Error::InvalidData("Insufficient data for header"));
+        }
+        let endian = match &data[..2] {
+            b"BY" => Endian::Big,
+            b"YB" => Endian::Little,
+            magic => {
+                return Err(Error::BadMagic(
+                    std::string::String::from_utf8_lossy(magic).into(),
+                    "BY or YB",
+                ));
+            }
+        };
+        let mut parser = Self {
+            data,
+            endian,
+            string_table: (0, 0),
+            hash_key_table: (0, 0),
+            root_node_offset: 0,
+        };
+        if !is_valid_version(parser.u16_at(2)?) {
+            return Err(Error::InvalidData("Unsupported BYML version (1-7 only)"));
+        }
+        parser.hash_key_table = parser.string_table_at(parser.u32_at(4)?)?;
+        parser.string_table = parser.string_table_at(parser.u32_at(8)?)?;
+        parser.root_node_offset = parser.u32_at(12)?;
+        Ok(parser)
+    }
+
+    #[inline(always)]
+    fn bytes_at<const N: usize>(&self, offset: u32) -> Result<[u8; N]> {
+        let offset = offset as usize;
+        offset
+            .checked_add(N)
+            .and_then(|end| self.data.get(offset..end))
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::InsufficientData(
+                self.data.len(),
+                offset.saturating_add(N),
+            ))
+    }
+
+    #[inline(always)]
+    fn slice_at(&self, offset: u32, len: u32) -> Result<&'a [u8]> {
+        let (offset, len) = (offset as usize, len as usize);
+        offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or(Error::InsufficientData(
+                self.data.len(),
+                offset.saturating_add(len),
+            ))
+    }
+
+    #[inline(always)]
+    fn u8_at(&self, offset: u32) -> Result<u8> {
+        Ok(self.bytes_at::<1>(offset)?[0])
+    }
+
+    #[inline(always)]
+    fn u16_at(&self, offset: u32) -> Result<u16> {
+        let bytes = self.bytes_at(offset)?;
+        Ok(match self.endian {
+            Endian::Big => u16::from_be_bytes(bytes),
+            Endian::Little => u16::from_le_bytes(bytes),
+        })
+    }
+
+    #[inline(always)]
+    fn u24_at(&self, offset: u32) -> Result<u32> {
+        let [a, b, c] = self.bytes_at(offset)?;
+        Ok(match self.endian {
+            Endian::Big => u32::from_be_bytes([0, a, b, c]),
+            Endian::Little => u32::from_le_bytes([a, b, c, 0]),
+        })
+    }
+
+    #[inline(always)]
+    fn u32_at(&self, offset: u32) -> Result<u32> {
+        let bytes = self.bytes_at(offset)?;
+        Ok(match self.endian {
+            Endian::Big => u32::from_be_bytes(bytes),
+            Endian::Little => u32::from_le_bytes(bytes),
+        })
+    }
+
+    #[inline(always)]
+    fn node_type_at(&self, offset: u32) -> Result<NodeType> {
+        NodeType::from_u8(self.u8_at(offset)?)
+    }
+
+    fn string_table_at(&self, offset: u32) -> Result<(u32, u32)> {
+        if offset == 0 {
+            return Ok((0, 0));
+        }
+        let type_ = self.node_type_at(offset)?;
+        if type_ != NodeType::StringTable {
+            return Err(Error::TypeError(
+                format!("{:?}", type_).into(),
+                "string table",
+            ));
+        }
+        Ok((offset, self.u24_at(offset_add(offset, 1)?)?))
+    }
+
+    fn get_string(&self, (table_offset, size): (u32, u32), index: u32) -> Result<String> {
+        if index >= size {
+            return Err(Error::InvalidData("Invalid string table entry index"));
+        }
+        let offset = self.u32_at(offset_add(table_offset, 4 + 4 * index as u64)?)?;
+        let next_offset = self.u32_at(offset_add(table_offset, 8 + 4 * index as u64)?)?;
+        let bytes = self.slice_at(
+            offset_add(table_offset, offset as u64)?,
+            next_offset.saturating_sub(offset),
+        )?;
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Ok(std::str::from_utf8(&bytes[..len])?.into())
+    }
+
+    fn dump_layout(&self) -> Result<std::string::String> {
+        let mut out = format!(
+            "{:#010x} magic: {} ({:?} endian)\n",
+            0,
+            std::string::String::from_utf8_lossy(&self.data[..2]),
+            self.endian
+        );
+        out.push_str(&format!("{:#010x} version: {}\n", 2, self.u16_at(2)?));
+        for (offset, name) in [
+            (4, "hash key table"),
+            (8, "string table"),
+            (12, "root node"),
+        ] {
+            out.push_str(&format!(
+                "{offset:#010x} {name} offset: {:#010x}\n",
+                self.u32_at(offset)?
+            ));
+        }
+        for (table, name) in [
+            (self.hash_key_table, "hash key table"),
+            (self.string_table, "string table"),
+        ] {
+            let (table_offset, size) = table;
+            if table_offset == 0 {
+                continue;
+            }
+            out.push_str(&format!("{table_offset:#010x} {name}: {size} entries\n"));
+            for i in 0..size {
+                let string_offset = offset_add(
+                    table_offset,
+                    self.u32_at(offset_add(table_offset, 4 + 4 * i as u64)?)? as u64,
+                )?;
+                out.push_str(&format!(
+                    "{string_offset:#010x}   [{i}] {:?}\n",
+                    self.get_string(table, i)?.as_str()
+                ));
+            }
+        }
+        if self.root_node_offset != 0 {
+            let mut visited = rustc_hash::FxHashSet::default();
+            self.dump_node(self.root_node_offset, 0, &mut visited, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn dump_node(
+        &self,
+        offset: u32,
+        depth: usize,
+        visited: &mut rustc_hash::FxHashSet<u32>,
+        out: &mut std::string::String,
+    ) -> Result<()> {
+        if depth > MAX_LAYOUT_DEPTH {
+            return Err(Error::InvalidData("BYML nodes are nested too deeply"));
+        }
+        let node_type = self.node_type_at(offset)?;
+        let size = self.u24_at(offset_add(offset, 1)?)?;
+        let indent = "  ".repeat(depth);
+        if !visited.insert(offset) {
+            out.push_str(&format!(
+                "{offset:#010x} {indent}{node_type:?} node (shared)\n"
+            ));
+            return Ok(());
+        }
+        out.push_str(&format!(
+            "{offset:#010x} {indent}{node_type:?} node: {size} entries\n"
+        ));
+        let children: Vec<(NodeType, u32)> = match node_type {
+            NodeType::Array => {
+                let values_offset = 4 + align(size, 4) as u64;
+                (0..size as u64)
+                    .map(|i| {
+                        Ok((
+                            self.node_type_at(offset_add(offset, 4 + i)?)?,
+                            offset_add(offset, values_offset + 4 * i)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            NodeType::Map => {
+                (0..size as u64)
+                    .map(|i| {
+                        Ok((
+                            self.node_type_at(offset_add(offset, 7 + 8 * i)?)?,
+                            offset_add(offset, 8 + 8 * i)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            NodeType::HashMap => {
+                (0..size as u64)
+                    .map(|i| {
+                        Ok((
+                            self.node_type_at(offset_add(offset, 4 + 8 * size as u64 + i)?)?,
+                            offset_add(offset, 8 + 8 * i)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            NodeType::ValueHashMap => {
+                (0..size as u64)
+                    .map(|i| {
+                        Ok((
+                            self.node_type_at(offset_add(offset, 4 + 12 * size as u64 + i)?)?,
+                            offset_add(offset, 4 + 12 * i)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            _ => {
+                return Err(Error::TypeError(
+                    format!("{node_type:?}").into(),
+                    "container node",
+                ));
+            }
+        };
+        for (child_type, value_offset) in children {
+            if is_container_type(child_type) {
+                self.dump_node(self.u32_at(value_offset)?, depth + 1, visited, out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -339,4 +666,66 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn from_binary_with_endian() {
+        let bytes = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        let byml = Byml::from_binary(&bytes).unwrap();
+        assert_eq!(
+            Byml::from_binary_with_endian(&bytes, Endian::Little).unwrap(),
+            byml
+        );
+        // Big endian data with a mismatched little endian magic
+        let mut be_bytes = byml.to_binary(Endian::Big);
+        be_bytes[..2].copy_from_slice(b"YB");
+        assert_eq!(
+            Byml::from_binary_with_endian(&be_bytes, Endian::Big).unwrap(),
+            byml
+        );
+    }
+
+    #[test]
+    fn root_before_tables() {
+        #[rustfmt::skip]
+        let data: &[u8] = &[
+            // Header, with the root node before the hash key and string tables
+            0x59, 0x42, 0x02, 0x00, 0x24, 0x00, 0x00, 0x00, 0x3C, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+            // 0x10: Root map node {"Arr": <array at 0x50>, "Key": "Value"}
+            0xC1, 0x02, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0xC0, 0x50, 0x00, 0x00, 0x00,
+            0x01, 0x00, 0x00, 0xA0, 0x00, 0x00, 0x00, 0x00,
+            // 0x24: Hash key table ["Arr", "Key"]
+            0xC2, 0x02, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00,
+            0x41, 0x72, 0x72, 0x00, 0x4B, 0x65, 0x79, 0x00,
+            // 0x3C: String table ["Value"]
+            0xC2, 0x01, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x12, 0x00, 0x00, 0x00,
+            0x56, 0x61, 0x6C, 0x75, 0x65, 0x00, 0x00, 0x00,
+            // 0x50: Array node [7], after the tables
+            0xC0, 0x01, 0x00, 0x00, 0xD1, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00,
+        ];
+        let expected = bmap!("Arr" => array!(Byml::I32(7)), "Key" => "Value");
+        assert_eq!(Byml::from_binary(data).unwrap(), expected);
+    }
+
+    #[test]
+    fn dump_layout() {
+        let bytes =
+            std::fs::read("test/byml/Mrg_01e57204_MrgD100_B4-B3-B2-1A90E17A.bcett.byml").unwrap();
+        let layout = Byml::dump_layout(&bytes).unwrap();
+        assert!(layout.starts_with("0x00000000 magic: YB (Little endian)"));
+        assert!(layout.contains("0x0000000c root node offset: "));
+        assert!(layout.contains(" string table: "));
+        assert!(layout.contains(" Map node: "));
+
+        // A hash key table whose string offsets overflow when added to its own
+        #[rustfmt::skip]
+        let data: &[u8] = &[
+            0x42, 0x59, 0x00, 0x02, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xC2, 0x00, 0x00, 0x01, 0xFF, 0xFF, 0xFF, 0xF0, 0xFF, 0xFF, 0xFF, 0xF8,
+        ];
+        assert!(matches!(
+            Byml::dump_layout(data),
+            Err(Error::InvalidData("BYML offset out of range"))
+        ));
+    }
 }
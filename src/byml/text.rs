@@ -128,6 +128,11 @@ impl<'a> Parser<'a> {
 
     fn parse(self) -> Result<Byml> {
         let root = self.0.root_ref()?;
+        if root.has_val_tag()? && root.val_tag()? == "!io" {
+            return Err(Error::InvalidDataD(
+                "Not a BYML YAML document (found AAMP !io root)".into(),
+            ));
+        }
         Self::parse_node(root)
     }
 }
@@ -326,4 +331,10 @@ mod test {
         let text2 = byml.to_text();
         assert!(text2.contains("0.0") && text2.contains("-0.0"));
     }
+
+    #[test]
+    fn aamp_root() {
+        let text = std::fs::read_to_string("test/aamp/test.yml").unwrap();
+        assert!(matches!(Byml::from_text(text), Err(Error::InvalidDataD(_))));
+    }
 }
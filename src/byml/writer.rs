@@ -13,6 +13,22 @@ use crate::{
     Endian, Error, Result,
 };
 
+/// Options for serializing a BYML document to binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BymlWriteOptions {
+    /// Write identical non-inline nodes (containers and binary data) only
+    /// once and point every reference at the same offset. This produces
+    /// smaller files; disable it to write every node in full. Defaults to
+    /// `true`.
+    pub dedup_nodes: bool,
+}
+
+impl Default for BymlWriteOptions {
+    fn default() -> Self {
+        Self { dedup_nodes: true }
+    }
+}
+
 impl Byml {
     /// Serialize the document to binary into the given writer. This can only
     /// be done for Null, Array, or Hash nodes.
@@ -21,6 +37,18 @@ impl Byml {
         writer: &mut W,
         endian: Endian,
         version: u16,
+    ) -> Result<()> {
+        self.write_with_options(writer, endian, version, Default::default())
+    }
+
+    /// Serialize the document to binary into the given writer using custom
+    /// write options. This can only be done for Null, Array, or Hash nodes.
+    pub fn write_with_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        version: u16,
+        options: BymlWriteOptions,
     ) -> Result<()> {
         if !is_valid_version(version) {
             return Err(Error::InvalidData("Unsupported BYML version (2-4 only)"));
@@ -36,7 +64,7 @@ impl Byml {
             ));
         }
 
-        let mut ctx = WriteContext::new(self, writer, endian);
+        let mut ctx = WriteContext::new(self, writer, endian, options);
         ctx.write(match endian {
             Endian::Little => b"YB",
             Endian::Big => b"BY",
@@ -89,6 +117,62 @@ impl Byml {
             .expect("BYML should serialize to binary without error");
         buf
     }
+
+    /// Serialize the document to BYML with the specified endianness, version
+    /// number, and write options. This can only be done for Null, Array, or
+    /// Hash nodes.
+    pub fn to_binary_with_options(
+        &self,
+        endian: Endian,
+        version: u16,
+        options: BymlWriteOptions,
+    ) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_with_options(&mut Cursor::new(&mut buf), endian, version, options)
+            .expect("BYML should serialize to binary without error");
+        buf
+    }
+
+    /// Serialize the document to BYML with the specified endianness and
+    /// version number, and compress the result with yaz0 at the given
+    /// compression level (6 to 9; 6 is fastest and 9 is slowest). This can
+    /// only be done for Null, Array, or Hash nodes.
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    pub fn to_binary_compressed(
+        &self,
+        endian: Endian,
+        version: u16,
+        level: u8,
+    ) -> Result<crate::Bytes> {
+        crate::yaz0::validate_level(level)?;
+        let mut buf = Vec::new();
+        self.write(&mut Cursor::new(&mut buf), endian, version)?;
+        Ok(crate::yaz0::compress_with_options(
+            buf,
+            crate::yaz0::CompressOptions {
+                compression_level: level,
+                ..Default::default()
+            },
+        ))
+    }
+
+    /// Returns the number of unique strings which would be written to the
+    /// string table if the document were serialized. Equal strings are only
+    /// stored once, however many times they occur in the document.
+    ///
+    /// Map keys are stored in a separate table and are not counted.
+    pub fn string_table_size(&self) -> usize {
+        let mut non_inline_node_count = 0;
+        let mut string_table = StringTable::default();
+        let mut hash_key_table = StringTable::default();
+        collect_tables(
+            self,
+            &mut non_inline_node_count,
+            &mut string_table,
+            &mut hash_key_table,
+        );
+        string_table.len()
+    }
 }
 
 struct NonInlineNode<'a> {
@@ -135,60 +219,59 @@ impl<'a> StringTable<'a> {
     }
 }
 
+fn collect_tables<'a>(
+    byml: &'a Byml,
+    count: &mut usize,
+    string_table: &mut StringTable<'a>,
+    hash_key_table: &mut StringTable<'a>,
+) {
+    match byml {
+        Byml::String(s) => {
+            string_table.add(s);
+        }
+        Byml::Array(arr) => {
+            for node in arr.iter() {
+                collect_tables(node, count, string_table, hash_key_table);
+            }
+        }
+        Byml::Map(hash) => {
+            for (key, node) in hash.iter() {
+                hash_key_table.add(key);
+                collect_tables(node, count, string_table, hash_key_table);
+            }
+        }
+        Byml::HashMap(hash) => {
+            for node in hash.values() {
+                collect_tables(node, count, string_table, hash_key_table);
+            }
+        }
+        Byml::ValueHashMap(hash) => {
+            for (node, _) in hash.values() {
+                collect_tables(node, count, string_table, hash_key_table);
+            }
+        }
+        Byml::BinaryData(_) | Byml::FileData(_) | Byml::I64(_) | Byml::U64(_) | Byml::Double(_) => {
+        }
+        _ => return,
+    }
+    *count += 1;
+}
+
 struct WriteContext<'a, W: Write + Seek> {
     writer: W,
     endian: binrw::Endian,
     hash_key_table: Rc<StringTable<'a>>,
     string_table: Rc<StringTable<'a>>,
     non_inline_node_data: FxHashMap<&'a Byml, u32>,
+    dedup_nodes: bool,
 }
 
 impl<'a, W: Write + Seek> WriteContext<'a, W> {
-    fn new(byml: &'a Byml, writer: W, endian: Endian) -> Self {
+    fn new(byml: &'a Byml, writer: W, endian: Endian, options: BymlWriteOptions) -> Self {
         let mut non_inline_node_count = 0;
         let mut string_table = StringTable::default();
         let mut hash_key_table = StringTable::default();
-        fn traverse<'a>(
-            byml: &'a Byml,
-            count: &mut usize,
-            string_table: &mut StringTable<'a>,
-            hash_key_table: &mut StringTable<'a>,
-        ) {
-            match byml {
-                Byml::String(s) => {
-                    string_table.add(s);
-                }
-                Byml::Array(arr) => {
-                    for node in arr.iter() {
-                        traverse(node, count, string_table, hash_key_table);
-                    }
-                }
-                Byml::Map(hash) => {
-                    for (key, node) in hash.iter() {
-                        hash_key_table.add(key);
-                        traverse(node, count, string_table, hash_key_table);
-                    }
-                }
-                Byml::HashMap(hash) => {
-                    for node in hash.values() {
-                        traverse(node, count, string_table, hash_key_table);
-                    }
-                }
-                Byml::ValueHashMap(hash) => {
-                    for (node, _) in hash.values() {
-                        traverse(node, count, string_table, hash_key_table);
-                    }
-                }
-                Byml::BinaryData(_)
-                | Byml::FileData(_)
-                | Byml::I64(_)
-                | Byml::U64(_)
-                | Byml::Double(_) => {}
-                _ => return,
-            }
-            *count += 1;
-        }
-        traverse(
+        collect_tables(
             byml,
             &mut non_inline_node_count,
             &mut string_table,
@@ -208,6 +291,7 @@ impl<'a, W: Write + Seek> WriteContext<'a, W> {
                 non_inline_node_count,
                 Default::default(),
             ),
+            dedup_nodes: options.dedup_nodes,
         }
     }
 
@@ -350,12 +434,19 @@ impl<'a, W: Write + Seek> WriteContext<'a, W> {
 
         for node in non_inline_nodes {
             self.align()?;
-            if let Some(pos) = self.non_inline_node_data.get(&node.data).copied() {
+            if let Some(pos) = self
+                .non_inline_node_data
+                .get(&node.data)
+                .copied()
+                .filter(|_| self.dedup_nodes)
+            {
                 self.write_at(pos, node.offset)?;
             } else {
                 let offset = self.writer.stream_position()? as u32;
                 self.write_at(offset, node.offset)?;
-                self.non_inline_node_data.insert(node.data, offset);
+                if self.dedup_nodes {
+                    self.non_inline_node_data.insert(node.data, offset);
+                }
                 match node.data {
                     Byml::Array(_) | Byml::Map(_) | Byml::HashMap(_) | Byml::ValueHashMap(_) => {
                         self.write_container_node(node.data)?
@@ -417,4 +508,87 @@ mod test {
             assert_eq!(byml, new_byml);
         }
     }
+
+    #[test]
+    fn string_table_size() {
+        let bytes = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        let byml = Byml::from_binary(bytes).unwrap();
+        assert_eq!(byml.string_table_size(), 63);
+        let doubled = Byml::Array(vec![byml.clone(), byml]);
+        assert_eq!(doubled.string_table_size(), 63);
+        assert_eq!(Byml::Null.string_table_size(), 0);
+    }
+
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    #[test]
+    fn to_binary_compressed() {
+        let bytes = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        let byml = Byml::from_binary(bytes).unwrap();
+        let compressed = byml.to_binary_compressed(Endian::Big, 3, 9).unwrap();
+        assert!(compressed.starts_with(b"Yaz0"));
+        assert_eq!(
+            crate::yaz0::decompress(&compressed).unwrap(),
+            byml.to_binary_with_version(Endian::Big, 3)
+        );
+        assert!(matches!(
+            byml.to_binary_compressed(Endian::Big, 3, 10),
+            Err(Error::InvalidDataD(_))
+        ));
+    }
+
+    #[test]
+    fn mixed_array() {
+        let byml = array!(
+            Byml::I32(1),
+            array!(Byml::Float(2.0), Byml::Null),
+            Byml::String("x".into()),
+            bmap!("Key" => "Value", "Nested" => array!(Byml::U32(3))),
+            Byml::Bool(true),
+        );
+        for endian in [Endian::Little, Endian::Big] {
+            let bytes = byml.to_binary(endian);
+            let read_u32 = |offset: usize| {
+                let data: [u8; 4] = bytes[offset..offset + 4].try_into().unwrap();
+                match endian {
+                    Endian::Little => u32::from_le_bytes(data),
+                    Endian::Big => u32::from_be_bytes(data),
+                }
+            };
+            let root = read_u32(0xC) as usize;
+            assert_eq!(bytes[root], 0xC0);
+            assert_eq!(&bytes[root + 4..root + 9], &[0xD1, 0xC0, 0xA0, 0xC1, 0xD0]);
+            // Values start after the type bytes, padded to 4 bytes
+            let values = root + 12;
+            assert_eq!(read_u32(values), 1);
+            assert_eq!(bytes[read_u32(values + 4) as usize], 0xC0);
+            assert_eq!(read_u32(values + 8), 1);
+            assert_eq!(bytes[read_u32(values + 12) as usize], 0xC1);
+            assert_eq!(read_u32(values + 16), 1);
+            assert_eq!(Byml::from_binary(bytes).unwrap(), byml);
+        }
+    }
+
+    #[test]
+    fn dedup_nodes() {
+        let entry = Byml::Map(
+            [
+                ("Name".into(), Byml::String("Enemy_Lizalfos_Senior".into())),
+                (
+                    "Translate".into(),
+                    Byml::Array(vec![Byml::Float(1.0), Byml::Float(2.0), Byml::Float(3.0)]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let byml = Byml::Array(vec![entry; 16]);
+        let deduped =
+            byml.to_binary_with_options(Endian::Little, 2, BymlWriteOptions { dedup_nodes: true });
+        let full =
+            byml.to_binary_with_options(Endian::Little, 2, BymlWriteOptions { dedup_nodes: false });
+        assert_eq!(deduped, byml.to_binary(Endian::Little));
+        assert!(deduped.len() < full.len());
+        assert_eq!(Byml::from_binary(deduped).unwrap(), byml);
+        assert_eq!(Byml::from_binary(full).unwrap(), byml);
+    }
 }
@@ -0,0 +1,105 @@
+//! One-call conversion between binary AAMP/BYML files and their YAML
+//! representations, as commonly needed by command line tools.
+//!
+//! ```
+//! # use roead::{convert::*, Endian};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let data = std::fs::read("test/aamp/Lizalfos.bphysics")?;
+//! let text = to_yaml(&data)?; // Format is detected automatically
+//! let data2 = from_yaml(&text, Format::Aamp, Endian::Little)?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::{aamp::ParameterIO, byml::Byml, Endian, Error, Result};
+
+/// Binary file formats which have a YAML representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// AAMP parameter archive
+    Aamp,
+    /// BYML document
+    Byml,
+}
+
+impl Format {
+    /// Detect the format of binary data from its magic, if it is known.
+    ///
+    /// **Note**: Yaz0 compressed data is not detected; decompress it first.
+    pub fn detect(data: &[u8]) -> Option<Format> {
+        if data.starts_with(b"AAMP") {
+            Some(Format::Aamp)
+        } else if data.starts_with(b"BY") || data.starts_with(b"YB") {
+            Some(Format::Byml)
+        } else {
+            None
+        }
+    }
+}
+
+/// Convert a binary AAMP or BYML file to YAML, detecting the format from the
+/// data.
+///
+/// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+/// this function automatically decompresses the data when necessary.
+pub fn to_yaml(data: &[u8]) -> Result<String> {
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    let data = crate::yaz0::decompress_if(data);
+    match Format::detect(&data) {
+        Some(Format::Aamp) => Ok(ParameterIO::from_binary(&data)?.to_text()),
+        Some(Format::Byml) => Ok(Byml::from_binary(&data)?.to_text()),
+        None => {
+            Err(Error::BadMagic(
+                String::from_utf8_lossy(&data[..data.len().min(4)]).into(),
+                "AAMP or BYML",
+            ))
+        }
+    }
+}
+
+/// Convert YAML text to a binary file of the given format.
+///
+/// BYML documents are written as version 2 with the given endianness. AAMP
+/// files are always little endian, so the endianness is ignored for them.
+pub fn from_yaml(text: &str, format: Format, endian: Endian) -> Result<Vec<u8>> {
+    match format {
+        Format::Aamp => Ok(ParameterIO::from_text(text)?.to_binary()),
+        Format::Byml => Ok(Byml::from_text(text)?.to_binary(endian)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aamp() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let text = to_yaml(&data).unwrap();
+        assert!(text.starts_with("!io"));
+        let data2 = from_yaml(&text, Format::Aamp, Endian::Little).unwrap();
+        assert_eq!(
+            ParameterIO::from_binary(data).unwrap(),
+            ParameterIO::from_binary(data2).unwrap()
+        );
+    }
+
+    #[test]
+    fn byml() {
+        let data = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        let text = to_yaml(&data).unwrap();
+        let data2 = from_yaml(&text, Format::Byml, Endian::Big).unwrap();
+        assert_eq!(data2[..2], *b"BY");
+        assert_eq!(
+            Byml::from_binary(data).unwrap(),
+            Byml::from_binary(data2).unwrap()
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert!(matches!(
+            to_yaml(b"SARC\0\0\0\0"),
+            Err(Error::BadMagic(_, "AAMP or BYML"))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! Fast header-only inspection of any supported file format.
+//!
+//! Reading a header does not parse or validate the rest of the file, and does
+//! not require the feature for the format in question to be enabled, which
+//! makes it useful for quickly triaging large numbers of files.
+use crate::{Endian, Error, Result};
+
+/// The minimal metadata found in the header of a supported file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileHeader {
+    /// SARC archive header
+    Sarc {
+        /// Byte order of the archive.
+        endian: Endian,
+        /// Format version (should be 0x100).
+        version: u16,
+        /// Number of files in the archive.
+        file_count: u16,
+        /// Offset to the beginning of the file data section.
+        data_offset: u32,
+    },
+    /// BYML document header
+    Byml {
+        /// Byte order of the document.
+        endian: Endian,
+        /// Format version.
+        version: u16,
+        /// Whether the document has a hash key table.
+        has_hash_key_table: bool,
+        /// Whether the document has a string table.
+        has_string_table: bool,
+    },
+    /// AAMP parameter archive header
+    Aamp {
+        /// Format version (should be 2).
+        version: u32,
+        /// Header flags (bit 0: little endian, bit 1: UTF-8).
+        flags: u32,
+        /// Number of parameter lists, including the root list.
+        list_count: u32,
+        /// Number of parameter objects.
+        object_count: u32,
+        /// Number of parameters.
+        param_count: u32,
+    },
+    /// Yaz0 compressed data header
+    Yaz0 {
+        /// The size of the data after decompression.
+        decompressed_size: u32,
+    },
+}
+
+#[inline(always)]
+fn read_u16(data: &[u8], offset: usize, endian: Endian) -> u16 {
+    let bytes = [data[offset], data[offset + 1]];
+    match endian {
+        Endian::Big => u16::from_be_bytes(bytes),
+        Endian::Little => u16::from_le_bytes(bytes),
+    }
+}
+
+#[inline(always)]
+fn read_u32(data: &[u8], offset: usize, endian: Endian) -> u32 {
+    let bytes = [
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ];
+    match endian {
+        Endian::Big => u32::from_be_bytes(bytes),
+        Endian::Little => u32::from_le_bytes(bytes),
+    }
+}
+
+#[inline(always)]
+fn ensure_len(data: &[u8], len: usize) -> Result<()> {
+    if data.len() < len {
+        Err(Error::InsufficientData(data.len(), len))
+    } else {
+        Ok(())
+    }
+}
+
+/// Read the header of a SARC, BYML, AAMP, or Yaz0 file, detecting the format
+/// from its magic. Only the header is read; the rest of the data is not
+/// checked.
+/// ```
+/// # use roead::{read_header, FileHeader};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("test/aamp/Lizalfos.bphysics")?;
+/// match read_header(&data)? {
+///     FileHeader::Aamp { param_count, .. } => assert_eq!(param_count, 275),
+///     _ => unreachable!(),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_header(data: &[u8]) -> Result<FileHeader> {
+    ensure_len(data, 4)?;
+    match &data[..4] {
+        b"SARC" => {
+            ensure_len(data, 0x20)?;
+            let endian = match &data[6..8] {
+                b"\xFE\xFF" => Endian::Big,
+                b"\xFF\xFE" => Endian::Little,
+                _ => return Err(Error::InvalidData("Invalid SARC byte order mark")),
+            };
+            Ok(FileHeader::Sarc {
+                endian,
+                version: read_u16(data, 0x10, endian),
+                file_count: read_u16(data, 0x1A, endian),
+                data_offset: read_u32(data, 0xC, endian),
+            })
+        }
+        b"AAMP" => {
+            ensure_len(data, 0x30)?;
+            Ok(FileHeader::Aamp {
+                version: read_u32(data, 0x4, Endian::Little),
+                flags: read_u32(data, 0x8, Endian::Little),
+                list_count: read_u32(data, 0x18, Endian::Little),
+                object_count: read_u32(data, 0x1C, Endian::Little),
+                param_count: read_u32(data, 0x20, Endian::Little),
+            })
+        }
+        b"Yaz0" => {
+            ensure_len(data, 0x10)?;
+            Ok(FileHeader::Yaz0 {
+                decompressed_size: read_u32(data, 0x4, Endian::Big),
+            })
+        }
+        magic if magic.starts_with(b"BY") || magic.starts_with(b"YB") => {
+            ensure_len(data, 0x10)?;
+            let endian = if magic.starts_with(b"BY") {
+                Endian::Big
+            } else {
+                Endian::Little
+            };
+            Ok(FileHeader::Byml {
+                endian,
+                version: read_u16(data, 0x2, endian),
+                has_hash_key_table: read_u32(data, 0x4, endian) != 0,
+                has_string_table: read_u32(data, 0x8, endian) != 0,
+            })
+        }
+        magic => {
+            Err(Error::BadMagic(
+                String::from_utf8_lossy(magic).into(),
+                "SARC, BYML, AAMP, or Yaz0",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sarc() {
+        let data = std::fs::read("test/sarc/test.sarc").unwrap();
+        assert_eq!(read_header(&data).unwrap(), FileHeader::Sarc {
+            endian: Endian::Big,
+            version: 0x100,
+            file_count: 6,
+            data_offset: 0x800,
+        });
+    }
+
+    #[test]
+    fn byml() {
+        let data = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        assert_eq!(read_header(&data).unwrap(), FileHeader::Byml {
+            endian: Endian::Little,
+            version: 2,
+            has_hash_key_table: true,
+            has_string_table: true,
+        });
+    }
+
+    #[test]
+    fn aamp() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        assert_eq!(read_header(&data).unwrap(), FileHeader::Aamp {
+            version: 2,
+            flags: 3,
+            list_count: 17,
+            object_count: 33,
+            param_count: 275,
+        });
+    }
+
+    #[test]
+    fn yaz0() {
+        let data = std::fs::read("test/yaz0/ActorInfo.product.sbyml").unwrap();
+        assert_eq!(read_header(&data).unwrap(), FileHeader::Yaz0 {
+            decompressed_size: 0x1DF654,
+        });
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            read_header(b"BNTX\0\0\0\0"),
+            Err(Error::BadMagic(_, "SARC, BYML, AAMP, or Yaz0"))
+        ));
+        assert!(matches!(
+            read_header(b"SARC"),
+            Err(Error::InsufficientData(4, 0x20))
+        ));
+    }
+}
@@ -27,8 +27,13 @@
 //! feature set includes `byml`, `aamp`, `sarc,` and `yaz0`. For compatibility
 //! with many existing tools for these formats, there is also a `yaml` feature
 //! which enables serializing/deserializing AAMP and BYML files as YAML
-//! documents. Finally, serde support is available using the `with-serde`
-//! feature.
+//! documents (plus a `convert` module with one-call conversion helpers).
+//! The optional `globset` feature enables selecting SARC files by glob
+//! pattern, the optional `mmap` feature enables loading SARC archives from
+//! memory-mapped files, the optional `rayon` feature enables extracting SARC
+//! archives in parallel, and the optional `derive` feature enables deriving
+//! `FromParameterObject` to read AAMP objects into structs. Finally, serde
+//! support is available using the `with-serde` feature.
 //!
 //! For API documentation, see the docs for each module.
 //!
@@ -45,6 +50,10 @@
 //! First, clone the repository, then enter the roead directory and run
 //! `git submodule update --init --recursive`.
 //!
+//! Alternatively, disable the default `yaz0` feature and enable `yaz0-rust`
+//! instead, which implements Yaz0 compression and decompression in pure Rust
+//! with none of the above requirements.
+//!
 //! ## Contributing
 //!
 //! Issue tracker: <https://github.com/NiceneNerd/roead/issues>  
@@ -54,18 +63,25 @@
 //! the GPLv2+ license.
 #![cfg_attr(all(doc, CHANNEL_NIGHTLY), feature(doc_auto_cfg))]
 #![cfg_attr(not(test), deny(clippy::unwrap_used))]
+// Allows using roead's own derive macros in its tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as roead;
 #[cfg(feature = "aamp")]
 pub mod aamp;
 #[cfg(feature = "byml")]
 pub mod byml;
+#[cfg(all(feature = "yaml", feature = "aamp", feature = "byml"))]
+pub mod convert;
+mod header;
 #[cfg(feature = "sarc")]
 pub mod sarc;
 pub mod types;
 mod util;
 #[cfg(feature = "yaml")]
-mod yaml;
-#[cfg(feature = "yaz0")]
+pub mod yaml;
+#[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
 pub mod yaz0;
+pub use header::{read_header, FileHeader};
 
 /// Error type for this crate.
 #[derive(Debug, thiserror::Error)]
@@ -97,6 +113,9 @@ pub enum Error {
     #[cfg(feature = "yaml")]
     #[error("Parsing YAML binary data failed: {0}")]
     InvalidYamlBinary(#[from] base64::DecodeError),
+    #[cfg(feature = "globset")]
+    #[error("Invalid glob pattern: {0}")]
+    InvalidGlob(#[from] globset::Error),
     #[cfg(feature = "yaz0")]
     #[error(transparent)]
     Yaz0Error(#[from] cxx::Exception),
@@ -125,3 +144,121 @@ impl Clone for Error {
         todo!()
     }
 }
+
+/// An owned buffer of binary data, as returned by the compression APIs (and
+/// anywhere else roead produces a fresh block of bytes).
+///
+/// `Bytes` dereferences to `[u8]`, so it can be used anywhere a byte slice is
+/// expected, and converting to or from a `Vec<u8>` is free.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes(Vec<u8>);
+
+impl Bytes {
+    /// Consumes the buffer, returning the underlying vector.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Borrows the buffer as a byte slice.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl std::ops::Deref for Bytes {
+    type Target = [u8];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.0.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl std::borrow::Borrow<[u8]> for Bytes {
+    #[inline(always)]
+    fn borrow(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    #[inline(always)]
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    #[inline(always)]
+    fn from(value: &[u8]) -> Self {
+        Self(value.to_vec())
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    #[inline(always)]
+    fn from(value: Bytes) -> Self {
+        value.0
+    }
+}
+
+impl<'a> From<Bytes> for std::borrow::Cow<'a, [u8]> {
+    #[inline(always)]
+    fn from(value: Bytes) -> Self {
+        std::borrow::Cow::Owned(value.0)
+    }
+}
+
+impl PartialEq<[u8]> for Bytes {
+    #[inline(always)]
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0.as_slice() == other
+    }
+}
+
+impl PartialEq<Vec<u8>> for Bytes {
+    #[inline(always)]
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        &self.0 == other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bytes;
+
+    #[test]
+    fn bytes_conversions() {
+        let vec = b"Yaz0 and SARC".to_vec();
+        let bytes = Bytes::from(vec.clone());
+        assert_eq!(bytes, vec);
+        assert_eq!(bytes.as_slice(), vec.as_slice());
+        assert_eq!(bytes.as_ref(), vec.as_slice());
+        assert_eq!(Bytes::from(vec.as_slice()), bytes);
+        let cow: std::borrow::Cow<'_, [u8]> = bytes.clone().into();
+        assert_eq!(cow.as_ref(), vec.as_slice());
+        assert_eq!(Vec::from(bytes.clone()), vec);
+        assert_eq!(bytes.into_vec(), vec);
+    }
+
+    #[test]
+    fn bytes_deref() {
+        let bytes = Bytes::from(b"SARC\xFE\xFF".to_vec());
+        assert_eq!(bytes.len(), 6);
+        assert!(bytes.starts_with(b"SARC"));
+        assert_eq!(&bytes[..4], b"SARC");
+        assert_eq!(bytes.iter().copied().max(), Some(0xFF));
+        let slice: &[u8] = &bytes;
+        assert_eq!(slice, b"SARC\xFE\xFF");
+        assert!(Bytes::default().is_empty());
+    }
+}
@@ -43,6 +43,8 @@
 //! ```
 mod parse;
 mod write;
+use std::collections::BTreeSet;
+
 use binrw::{binrw, BinRead, BinWrite};
 pub use parse::Sarc;
 pub use write::SarcWriter;
@@ -98,10 +100,10 @@ impl<'a> File<'a> {
         self.index
     }
 
-    #[cfg(feature = "yaz0")]
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
     /// Returns a decompressed copy of the file data.
     #[inline(always)]
-    pub fn decompressed_data(&self) -> crate::Result<Vec<u8>> {
+    pub fn decompressed_data(&self) -> crate::Result<crate::Bytes> {
         crate::yaz0::decompress(self.data)
     }
 
@@ -140,8 +142,107 @@ impl<'a> File<'a> {
     }
 }
 
+/// A file from a SARC archive, parsed according to its format. Returned by
+/// [`Sarc::iter_parsed`].
+#[derive(Debug, PartialEq)]
+pub enum ParsedFile<'a> {
+    /// BYML document.
+    #[cfg(feature = "byml")]
+    Byml(crate::byml::Byml),
+    /// AAMP parameter archive.
+    #[cfg(feature = "aamp")]
+    Aamp(crate::aamp::ParameterIO),
+    /// Nested SARC archive, decompressed if necessary (so it can be parsed
+    /// with [`Sarc::new_from_decompressed`]).
+    Sarc(Vec<u8>),
+    /// Any other file, or one which failed to parse.
+    Raw(&'a [u8]),
+}
+
+impl<'a> ParsedFile<'a> {
+    fn parse(data: &'a [u8]) -> Self {
+        // The data is decompressed once here, and then read directly so it is
+        // not checked for compression again.
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        let decompressed = crate::yaz0::decompress_if(data);
+        #[cfg(not(any(feature = "yaz0", feature = "yaz0-rust")))]
+        let decompressed = std::borrow::Cow::Borrowed(data);
+        let bytes = decompressed.as_ref();
+        #[cfg(feature = "byml")]
+        if bytes.starts_with(b"BY") || bytes.starts_with(b"YB") {
+            if let Ok(byml) = crate::byml::Byml::read(std::io::Cursor::new(bytes)) {
+                return ParsedFile::Byml(byml);
+            }
+        }
+        #[cfg(feature = "aamp")]
+        if bytes.starts_with(b"AAMP") {
+            if let Ok(pio) = crate::aamp::ParameterIO::read(std::io::Cursor::new(bytes)) {
+                return ParsedFile::Aamp(pio);
+            }
+        }
+        if bytes.starts_with(b"SARC") {
+            return ParsedFile::Sarc(decompressed.into_owned());
+        }
+        ParsedFile::Raw(data)
+    }
+}
+
+/// Number of bytes taken up by each section of a SARC archive. Returned by
+/// [`Sarc::size_breakdown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SarcSizeBreakdown {
+    /// SARC header.
+    pub header: usize,
+    /// File allocation table (SFAT header and entries).
+    pub sfat: usize,
+    /// File name table (SFNT header and names).
+    pub sfnt: usize,
+    /// Alignment padding, after the name table and between or after files.
+    pub padding: usize,
+    /// File data.
+    pub data: usize,
+}
+
+impl SarcSizeBreakdown {
+    /// Total size of all sections.
+    pub fn total(&self) -> usize {
+        self.header + self.sfat + self.sfnt + self.padding + self.data
+    }
+}
+
+/// Names of the files which differ between two SARC archives. Returned by
+/// [`Sarc::diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SarcDiff {
+    /// Files which are only in the other archive.
+    pub added: BTreeSet<String>,
+    /// Files which are only in this archive.
+    pub removed: BTreeSet<String>,
+    /// Files which are in both archives but with different data.
+    pub modified: BTreeSet<String>,
+}
+
+impl SarcDiff {
+    /// Returns true if the archives contain the same files with the same
+    /// data.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Hash a file name as stored in the SFAT section of a SARC archive. The hash
+/// multiplier is stored in the archive header; BOTW and every writer in this
+/// crate use `0x65`.
+///
+/// ```
+/// # use roead::sarc::sarc_hash_name;
+/// assert_eq!(
+///     sarc_hash_name(0x65, "NavMesh/CDungeon/Dungeon119/Dungeon119.shknm2"),
+///     0x4A6D0241
+/// );
+/// ```
 #[inline]
-const fn hash_name(multiplier: u32, name: &str) -> u32 {
+pub const fn sarc_hash_name(multiplier: u32, name: &str) -> u32 {
     let mut hash = 0u32;
     let bytes = name.as_bytes();
     let mut i = 0;
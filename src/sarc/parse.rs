@@ -3,11 +3,14 @@ use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
     io::Cursor,
+    ops::Deref,
+    path::{Component, Path, PathBuf},
 };
 
 use binrw::{BinRead, BinReaderExt};
 use join_str::jstr;
 use num_integer::Integer;
+use rustc_hash::FxHashMap;
 
 use super::*;
 use crate::{Error, Result};
@@ -78,6 +81,68 @@ impl<'a> Iterator for FileIterator<'a> {
     }
 }
 
+/// Backing storage for the data of a [`Sarc`].
+#[derive(Clone)]
+enum SarcData<'a> {
+    Cow(Cow<'a, [u8]>),
+    #[cfg(feature = "mmap")]
+    Mmap(std::sync::Arc<memmap2::Mmap>),
+}
+
+impl Deref for SarcData<'_> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Cow(data) => data,
+            #[cfg(feature = "mmap")]
+            Self::Mmap(mmap) => mmap,
+        }
+    }
+}
+
+/// Get the path a file is extracted to by [`Sarc::extract_to_dir`], checking
+/// that it does not escape the output directory.
+fn extract_path(dir: &Path, file: &File<'_>) -> Result<PathBuf> {
+    match file.name {
+        Some(name) => {
+            if !Path::new(name)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+            {
+                return Err(Error::InvalidDataD(jstr!(
+                    "SARC file name escapes the output directory: {name}"
+                )));
+            }
+            Ok(dir.join(name))
+        }
+        None => Ok(dir.join(format!("__unnamed_{}", file.index))),
+    }
+}
+
+/// Compare two sets of files by name and data for [`Sarc::diff`].
+fn diff_files(files: &FxHashMap<&str, &[u8]>, other_files: &FxHashMap<&str, &[u8]>) -> SarcDiff {
+    let mut diff = SarcDiff::default();
+    for (name, data) in files.iter() {
+        match other_files.get(name) {
+            Some(other_data) if other_data != data => {
+                diff.modified.insert(name.to_string());
+            }
+            Some(_) => (),
+            None => {
+                diff.removed.insert(name.to_string());
+            }
+        }
+    }
+    diff.added = other_files
+        .keys()
+        .filter(|name| !files.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect();
+    diff
+}
+
 #[derive(Clone)]
 /// A simple SARC archive reader
 pub struct Sarc<'a> {
@@ -86,8 +151,9 @@ pub struct Sarc<'a> {
     hash_multiplier: u32,
     data_offset: u32,
     names_offset: u32,
+    reserved: u16,
     endian: Endian,
-    data: Cow<'a, [u8]>,
+    data: SarcData<'a>,
 }
 
 impl std::fmt::Debug for Sarc<'_> {
@@ -98,6 +164,7 @@ impl std::fmt::Debug for Sarc<'_> {
             .field("hash_multiplier", &self.hash_multiplier)
             .field("data_offset", &self.data_offset)
             .field("names_offset", &self.names_offset)
+            .field("reserved", &self.reserved)
             .field("endian", &self.endian)
             .finish()
     }
@@ -106,7 +173,7 @@ impl std::fmt::Debug for Sarc<'_> {
 impl PartialEq for Sarc<'_> {
     /// Returns true if and only if the raw archive data is identical
     fn eq(&self, other: &Self) -> bool {
-        self.data == other.data
+        *self.data == *other.data
     }
 }
 
@@ -114,7 +181,7 @@ impl Eq for Sarc<'_> {}
 
 impl Hash for Sarc<'_> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.data.hash(state)
+        (*self.data).hash(state)
     }
 }
 
@@ -130,19 +197,59 @@ impl<'a, S: std::borrow::Borrow<str>> std::ops::Index<S> for Sarc<'a> {
 impl<'a> Sarc<'_> {
     /// Parses a SARC archive from binary data.
     ///
-    /// **Note**: If and only if the `yaz0` feature is enabled, this function
-    /// automatically decompresses the SARC when necessary.
+    /// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+    /// this function automatically decompresses the SARC when necessary.
     pub fn new<T: Into<Cow<'a, [u8]>>>(data: T) -> crate::Result<Sarc<'a>> {
         let mut data = data.into();
 
-        #[cfg(feature = "yaz0")]
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
         {
             if data.starts_with(b"Yaz0") {
                 data = crate::yaz0::decompress(&data)?.into();
             }
         }
 
-        let mut reader = Cursor::new(data.as_ref());
+        Self::new_from_decompressed(data)
+    }
+
+    /// Parses a SARC archive from binary data which is known to already be
+    /// decompressed (for example, the output of a previous
+    /// `yaz0::decompress` call), skipping the check for Yaz0
+    /// compression. Compressed data is not detected and fails to parse.
+    pub fn new_from_decompressed<T: Into<Cow<'a, [u8]>>>(data: T) -> crate::Result<Sarc<'a>> {
+        Self::from_data(SarcData::Cow(data.into()))
+    }
+
+    /// Parses a SARC archive from a memory-mapped file, borrowing the mapped
+    /// bytes instead of reading the whole file into memory. This is much
+    /// cheaper for browsing large archives.
+    ///
+    /// **Note**: If and only if the `yaz0` or `yaz0-rust` feature is enabled,
+    /// compressed archives are supported, but they are decompressed into an
+    /// owned buffer as with [`Sarc::new`].
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated, by this or any other
+    /// process, while the archive or any data borrowed from it is in use.
+    /// Otherwise the mapped data could change underneath it, which is
+    /// undefined behavior.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_mmap<P: AsRef<Path>>(path: P) -> crate::Result<Sarc<'static>> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: The map is read-only, and the caller guarantees that the file
+        // is not modified while it is mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        {
+            if mmap.starts_with(b"Yaz0") {
+                return Sarc::new_from_decompressed(crate::yaz0::decompress(&*mmap)?);
+            }
+        }
+        Sarc::from_data(SarcData::Mmap(std::sync::Arc::new(mmap)))
+    }
+
+    fn from_data(data: SarcData<'_>) -> crate::Result<Sarc<'_>> {
+        let mut reader = Cursor::new(&*data);
         reader.set_position(6);
         let endian: Endian = Endian::read_ne(&mut reader).map_err(Error::from)?;
         reader.set_position(0);
@@ -189,9 +296,40 @@ impl<'a> Sarc<'_> {
             num_files,
             hash_multiplier,
             names_offset,
+            reserved: header.reserved,
         })
     }
 
+    /// Parses a SARC archive from binary data like [`Sarc::new`], but also
+    /// checks up front that the data range of every file lies within the
+    /// archive, so that corrupt archives are rejected at construction instead
+    /// of failing when the file is accessed.
+    pub fn new_validated<T: Into<Cow<'a, [u8]>>>(data: T) -> crate::Result<Sarc<'a>> {
+        let sarc = Sarc::new(data)?;
+        sarc.validate_entries()?;
+        Ok(sarc)
+    }
+
+    fn validate_entries(&self) -> Result<()> {
+        let entries_end =
+            self.entries_offset as usize + size_of::<ResFatEntry>() * self.num_files as usize;
+        if entries_end > self.data.len() {
+            return Err(Error::InsufficientData(self.data.len(), entries_end));
+        }
+        let mut reader = Cursor::new(&self.data[self.entries_offset as usize..]);
+        for index in 0..self.num_files as usize {
+            let entry: ResFatEntry = read(self.endian, &mut reader)?;
+            let begin = self.data_offset as usize + entry.data_begin as usize;
+            let end = self.data_offset as usize + entry.data_end as usize;
+            if begin > end || end > self.data.len() {
+                return Err(Error::InvalidDataD(jstr!(
+                    "Data for file at index {&index.to_string()} is out of bounds in SARC"
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Get the number of files that are stored in the archive
     pub fn len(&self) -> usize {
         self.num_files as usize
@@ -202,6 +340,13 @@ impl<'a> Sarc<'_> {
         self.num_files == 0
     }
 
+    /// Get the raw (decompressed) archive data, e.g. to write an unmodified
+    /// archive back out without rebuilding it.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
     /// Get the offset to the beginning of file data
     pub fn data_offset(&self) -> usize {
         self.data_offset as usize
@@ -212,15 +357,27 @@ impl<'a> Sarc<'_> {
         self.endian
     }
 
+    /// Get the value of the reserved field in the archive header. This is
+    /// normally 0, but some archives set it.
+    pub fn reserved(&self) -> u16 {
+        self.reserved
+    }
+
+    /// Get the multiplier used to hash file names in the archive. This is
+    /// 0x65 for BOTW archives but may differ in other games.
+    pub fn hash_multiplier(&self) -> u32 {
+        self.hash_multiplier
+    }
+
     #[inline(always)]
     fn find_file(&self, file: &str) -> Result<Option<usize>> {
         if self.num_files == 0 {
             return Ok(None);
         }
-        let needle_hash = hash_name(self.hash_multiplier, file);
+        let needle_hash = sarc_hash_name(self.hash_multiplier, file);
         let mut a: u32 = 0;
         let mut b: u32 = self.num_files as u32 - 1;
-        let mut reader = Cursor::new(self.data.as_ref());
+        let mut reader = Cursor::new(&*self.data);
         while a <= b {
             let m: u32 = (a + b) / 2;
             reader.set_position(self.entries_offset as u64 + 0x10 * m as u64);
@@ -322,6 +479,109 @@ impl<'a> Sarc<'_> {
         }
     }
 
+    /// Returns an iterator over just the names of the contained files, in the
+    /// same order as [`Sarc::files`]. This only reads the file allocation
+    /// table and name table, so it is cheaper than [`Sarc::files`] when the
+    /// file data is not needed.
+    pub fn file_names(&self) -> impl Iterator<Item = Option<&str>> {
+        (0..self.num_files as usize).map_while(move |index| {
+            let offset = self.entries_offset as usize + size_of::<ResFatEntry>() * index + 4;
+            let bytes = self.data.get(offset..offset + 4)?.try_into().ok()?;
+            let rel_name_opt_offset = match self.endian {
+                Endian::Big => u32::from_be_bytes(bytes),
+                Endian::Little => u32::from_le_bytes(bytes),
+            };
+            if rel_name_opt_offset == 0 {
+                return Some(None);
+            }
+            let name_offset =
+                self.names_offset as usize + (rel_name_opt_offset & 0xFFFFFF) as usize * 4;
+            let term_pos = find_null(self.data.get(name_offset..)?).ok()?;
+            std::str::from_utf8(&self.data[name_offset..name_offset + term_pos])
+                .ok()
+                .map(Some)
+        })
+    }
+
+    /// Get the files in the archive sorted by name, for human-friendly
+    /// listings. [`Sarc::files`] instead yields them in the order they are
+    /// stored, which is sorted by name hash. Files without a name come last,
+    /// in stored order.
+    pub fn files_name_sorted(&self) -> Vec<File<'_>> {
+        let mut files = self.files().collect::<Vec<_>>();
+        files.sort_by(|a, b| (a.name.is_none(), a.name).cmp(&(b.name.is_none(), b.name)));
+        files
+    }
+
+    #[inline]
+    fn entry_has_name(&self, index: usize) -> bool {
+        let entry_offset = self.entries_offset as usize + size_of::<ResFatEntry>() * index;
+        read::<ResFatEntry>(self.endian, &mut Cursor::new(&self.data[entry_offset..]))
+            .map(|entry| entry.rel_name_opt_offset != 0)
+            .unwrap_or(false)
+    }
+
+    /// Check whether any file in the archive has a name in the file name
+    /// table. If not, files can only be looked up by index.
+    pub fn has_names(&self) -> bool {
+        (0..self.num_files as usize).any(|i| self.entry_has_name(i))
+    }
+
+    /// Check whether every file in the archive has a name in the file name
+    /// table, so that all of them can be looked up by name. This is `true`
+    /// for an empty archive.
+    pub fn all_named(&self) -> bool {
+        (0..self.num_files as usize).all(|i| self.entry_has_name(i))
+    }
+
+    /// Returns an iterator over the files in the archive which are yaz0
+    /// compressed.
+    pub fn compressed_files(&self) -> impl Iterator<Item = File<'_>> {
+        self.files().filter(|file| file.is_compressed())
+    }
+
+    /// Get the number of files in the archive which are yaz0 compressed.
+    pub fn compressed_file_count(&self) -> usize {
+        self.compressed_files().count()
+    }
+
+    /// Returns an iterator over the named files whose names match a glob
+    /// pattern, like `Map/**/*.smubin`. A `*` does not match across `/`
+    /// separators, while `**` matches any number of path components.
+    ///
+    /// This is only available with the `globset` feature.
+    #[cfg(feature = "globset")]
+    pub fn files_glob(&self, pattern: &str) -> Result<impl Iterator<Item = File<'_>>> {
+        let matcher = globset::GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()?
+            .compile_matcher();
+        Ok(self.files().filter(move |file| {
+            file.name
+                .map(|name| matcher.is_match(name))
+                .unwrap_or(false)
+        }))
+    }
+
+    /// Get the total size of the files in the archive once any yaz0
+    /// compressed files are decompressed, for example to estimate disk usage
+    /// before extracting. Only the yaz0 headers are read; nothing is actually
+    /// decompressed.
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    pub fn total_decompressed_size(&self) -> Result<usize> {
+        self.files()
+            .map(|file| {
+                if file.is_compressed() {
+                    crate::yaz0::get_header(file.data)
+                        .map(|header| header.uncompressed_size as usize)
+                        .ok_or(Error::InsufficientData(file.data.len(), 0x10))
+                } else {
+                    Ok(file.data.len())
+                }
+            })
+            .sum()
+    }
+
     /// Guess the minimum data alignment for files that are stored in the
     /// archive
     pub fn guess_min_alignment(&self) -> usize {
@@ -340,6 +600,211 @@ impl<'a> Sarc<'_> {
         gcd as usize
     }
 
+    /// Infer the data alignment of each file in the archive, returning each
+    /// file's name with the largest power of two which divides the absolute
+    /// offset of its data (or the result of [`Sarc::guess_min_alignment`] if
+    /// that offset is zero).
+    ///
+    /// Unlike [`Sarc::guess_min_alignment`], this gives per-file results,
+    /// which is useful for configuring a [`SarcWriter`] to faithfully repack
+    /// the archive.
+    pub fn file_alignments(&self) -> Vec<(Option<&str>, usize)> {
+        let min_alignment = self.guess_min_alignment();
+        self.files()
+            .map(|file| {
+                let offset = file.data.as_ptr() as usize - self.data.as_ptr() as usize;
+                let alignment = 1usize
+                    .checked_shl(offset.trailing_zeros())
+                    .unwrap_or(min_alignment);
+                (file.name, alignment)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the file names and contents of the archive,
+    /// with each file parsed according to its format where it is recognized
+    /// (decompressing it first if needed and the `yaz0` or `yaz0-rust`
+    /// feature is enabled). Files which cannot be parsed are returned as
+    /// [`ParsedFile::Raw`].
+    pub fn iter_parsed(&self) -> impl Iterator<Item = (Option<&str>, ParsedFile<'_>)> {
+        self.files()
+            .map(|file| (file.name, ParsedFile::parse(file.data)))
+    }
+
+    /// Convert every AAMP and BYML file in the archive to YAML (decompressing
+    /// it first if needed and the `yaz0` or `yaz0-rust` feature is enabled),
+    /// pairing each result with the file name. Files without names are listed
+    /// as `__unnamed_{index}`, and files in any other format are skipped.
+    #[cfg(all(feature = "yaml", feature = "aamp", feature = "byml"))]
+    pub fn dump_convertible(&self) -> Vec<(String, Result<String>)> {
+        self.files()
+            .filter_map(|file| {
+                #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+                let data = crate::yaz0::decompress_if(file.data);
+                #[cfg(not(any(feature = "yaz0", feature = "yaz0-rust")))]
+                let data = file.data;
+                crate::convert::Format::detect(&data)?;
+                let name = match file.name {
+                    Some(name) => name.to_owned(),
+                    None => format!("__unnamed_{}", file.index),
+                };
+                Some((name, crate::convert::to_yaml(&data)))
+            })
+            .collect()
+    }
+
+    /// Read the header of each file in the archive with [`crate::read_header`]
+    /// (decompressing it first if needed and the `yaz0` or `yaz0-rust` feature
+    /// is enabled), pairing each with the file name, for example to check the
+    /// endianness and versions of a pack's members. Files in unrecognized
+    /// formats have no header, and files without names are listed as
+    /// `__unnamed_{index}`.
+    pub fn member_formats(&self) -> Vec<(String, Option<crate::FileHeader>)> {
+        self.files()
+            .map(|file| {
+                #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+                let data = crate::yaz0::decompress_if(file.data);
+                #[cfg(not(any(feature = "yaz0", feature = "yaz0-rust")))]
+                let data = Cow::Borrowed(file.data);
+                let name = match file.name {
+                    Some(name) => name.to_owned(),
+                    None => format!("__unnamed_{}", file.index),
+                };
+                (name, crate::read_header(&data).ok())
+            })
+            .collect()
+    }
+
+    /// Compute how much of the archive is taken up by each section (header,
+    /// SFAT, SFNT, padding, and file data), e.g. to see where the space in an
+    /// archive goes.
+    ///
+    /// Any part of the data section which is not covered by a readable SFAT
+    /// entry is counted as padding, and entries pointing past the end of the
+    /// archive are cut off there.
+    pub fn size_breakdown(&self) -> SarcSizeBreakdown {
+        let mut reader = Cursor::new(
+            self.data
+                .get(self.entries_offset as usize..)
+                .unwrap_or_default(),
+        );
+        let mut names_end = self.names_offset as usize;
+        let mut ranges = Vec::with_capacity(self.num_files as usize);
+        for _ in 0..self.num_files {
+            let entry: ResFatEntry = match read(self.endian, &mut reader) {
+                Ok(entry) => entry,
+                Err(_) => break,
+            };
+            if entry.rel_name_opt_offset != 0 {
+                let name_offset = self.names_offset as usize
+                    + (entry.rel_name_opt_offset & 0xFFFFFF) as usize * 4;
+                if let Some(term_pos) = self
+                    .data
+                    .get(name_offset..)
+                    .and_then(|names| find_null(names).ok())
+                {
+                    names_end = names_end.max(name_offset + term_pos + 1);
+                }
+            }
+            let offset = |rel: u32| {
+                (self.data_offset as usize)
+                    .saturating_add(rel as usize)
+                    .min(self.data.len())
+            };
+            ranges.push(offset(entry.data_begin)..offset(entry.data_end));
+        }
+        ranges.sort_by_key(|range| range.start);
+
+        let mut breakdown = SarcSizeBreakdown {
+            header: 0x14,
+            sfat: 0x0C + size_of::<ResFatEntry>() * self.num_files as usize,
+            sfnt: 0x08 + (names_end - self.names_offset as usize),
+            padding: (self.data_offset as usize).saturating_sub(names_end),
+            data: 0,
+        };
+        let mut pos = self.data_offset as usize;
+        for range in ranges {
+            breakdown.padding += range.start.saturating_sub(pos);
+            breakdown.data += range.end.saturating_sub(range.start.max(pos));
+            pos = pos.max(range.end);
+        }
+        breakdown.padding += self.data.len().saturating_sub(pos);
+        breakdown
+    }
+
+    /// Extract every file in the archive into a directory, creating
+    /// subdirectories as needed from the file names. Files without names are
+    /// written as `__unnamed_{index}`.
+    ///
+    /// Returns an error if a file name would escape the target directory
+    /// (e.g. an absolute path or one containing `..`), or if any file cannot
+    /// be written. Files extracted before the failure are left in place.
+    pub fn extract_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        for file in self.files() {
+            let dest = extract_path(dir, &file)?;
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(dest, file.data)?;
+        }
+        Ok(())
+    }
+
+    /// Extract every file in the archive into a directory like
+    /// [`Sarc::extract_to_dir`], but write the files in parallel. The
+    /// subdirectories are created up front, before any file is written.
+    ///
+    /// Returns an error if a file name would escape the target directory, in
+    /// which case nothing is written, or if any file cannot be written.
+    #[cfg(feature = "rayon")]
+    pub fn par_extract_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        use rayon::prelude::*;
+
+        let dir = dir.as_ref();
+        let files = self
+            .files()
+            .map(|file| Ok((extract_path(dir, &file)?, file.data)))
+            .collect::<Result<Vec<_>>>()?;
+        let parents: rustc_hash::FxHashSet<&Path> =
+            files.iter().filter_map(|(dest, _)| dest.parent()).collect();
+        for parent in parents {
+            std::fs::create_dir_all(parent)?;
+        }
+        files.into_par_iter().try_for_each(|(dest, data)| {
+            std::fs::write(dest, data)?;
+            Ok(())
+        })
+    }
+
+    /// Compare the files in this archive with those in another one by name and
+    /// data. Files without names are ignored.
+    pub fn diff(&self, other: &Sarc<'_>) -> SarcDiff {
+        diff_files(&self.named_files(), &other.named_files())
+    }
+
+    /// Compare the files in this archive with the files in a directory tree,
+    /// such as one produced by [`Sarc::extract_to_dir`], by name and data.
+    /// Files on disk are named by their paths relative to `dir` as with
+    /// [`SarcWriter::from_directory`]. In the result, `added` lists the files
+    /// which are only on disk and `removed` those which are only in the
+    /// archive. Files without names are ignored.
+    pub fn diff_directory<P: AsRef<Path>>(&self, dir: P) -> Result<SarcDiff> {
+        let writer = SarcWriter::from_directory(dir, self.endian)?;
+        let disk_files = writer
+            .files
+            .iter()
+            .map(|(name, data)| (name.as_str(), data.as_slice()))
+            .collect();
+        Ok(diff_files(&self.named_files(), &disk_files))
+    }
+
+    fn named_files(&self) -> FxHashMap<&str, &[u8]> {
+        self.files()
+            .filter_map(|file| file.name.map(|name| (name, file.data)))
+            .collect()
+    }
+
     /// Returns true is each archive contains the same files
     pub fn are_files_equal(sarc1: &Sarc, sarc2: &Sarc) -> bool {
         if sarc1.len() != sarc2.len() {
@@ -383,4 +848,393 @@ mod tests {
                 .unwrap_or_else(|| panic!("Could not find file {}", file));
         }
     }
+
+    #[test]
+    fn extract_to_dir() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let dir = std::env::temp_dir().join("roead_extract_to_dir");
+        let _ = std::fs::remove_dir_all(&dir);
+        sarc.extract_to_dir(&dir).unwrap();
+        for file in sarc.files() {
+            assert_eq!(read(dir.join(file.unwrap_name())).unwrap(), file.data);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let data = crate::sarc::SarcWriter::new(Endian::Little)
+            .with_file("../Escape.txt", b"nope".as_slice())
+            .to_binary();
+        let sarc = Sarc::new(data).unwrap();
+        assert!(sarc.extract_to_dir(&dir).is_err());
+        assert!(!dir.with_file_name("Escape.txt").exists());
+    }
+
+    #[test]
+    fn member_formats() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let formats = sarc.member_formats();
+        assert_eq!(formats.len(), sarc.len());
+        let header = formats
+            .iter()
+            .find(|(name, _)| name == "Map/CDungeon/Dungeon119/Dungeon119_Static.smubin")
+            .and_then(|(_, header)| *header);
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        assert!(matches!(
+            header,
+            Some(crate::FileHeader::Byml {
+                endian: Endian::Big,
+                version: 2,
+                ..
+            })
+        ));
+        #[cfg(not(any(feature = "yaz0", feature = "yaz0-rust")))]
+        assert!(matches!(header, Some(crate::FileHeader::Yaz0 { .. })));
+    }
+
+    #[cfg(all(feature = "yaml", feature = "aamp", feature = "byml"))]
+    #[test]
+    fn dump_convertible() {
+        let data = read("test/sarc/ActorObserverByActorTagTag.sarc").unwrap();
+        let data = crate::sarc::SarcWriter::from_sarc(&Sarc::new(&data).unwrap())
+            .with_file(
+                "Map/Test.byml",
+                crate::byml::Byml::Array(vec![1.into()]).to_binary(Endian::Little),
+            )
+            .with_file("Text.txt", b"Plain text".as_slice())
+            .with_file("Broken.bxml", b"AAMP".as_slice())
+            .to_binary();
+        let sarc = Sarc::new(data).unwrap();
+        let dump = sarc.dump_convertible();
+        assert_eq!(dump.len(), 5);
+        for (name, text) in dump {
+            match name.as_str() {
+                "Broken.bxml" => assert!(text.is_err()),
+                _ => assert!(!text.unwrap().is_empty()),
+            }
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn from_mmap() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = unsafe { Sarc::from_mmap("test/sarc/Dungeon119.pack") }.unwrap();
+        assert_eq!(sarc, Sarc::new(&data).unwrap());
+        let file = sarc
+            .get("Map/DungeonData/CDungeon/Dungeon119.bdgnenv")
+            .unwrap();
+        assert!(file.data.starts_with(b"AAMP"));
+        assert!(unsafe { Sarc::from_mmap("test/sarc/Missing.pack") }.is_err());
+    }
+
+    #[test]
+    fn as_bytes() {
+        let data = read("test/sarc/test.sarc").unwrap();
+        assert_eq!(Sarc::new(&data).unwrap().as_bytes(), data.as_slice());
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        {
+            let compressed = crate::yaz0::compress(&data);
+            assert_eq!(Sarc::new(compressed).unwrap().as_bytes(), data.as_slice());
+        }
+    }
+
+    #[test]
+    fn diff() {
+        let data = read("test/sarc/ActorObserverByActorTagTag.sarc").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert!(sarc.diff(&sarc).is_empty());
+
+        let mut writer = crate::sarc::SarcWriter::from_sarc(&sarc);
+        writer.remove_file("Actor/ModelList/ActorObserverTag.bmodellist");
+        writer
+            .files
+            .get_mut("Actor/AIProgram/ActorObserverByActorTagTag.baiprog")
+            .unwrap()
+            .push(0);
+        writer.add_file("Actor/Physics/New.bphysics", b"AAMP".as_slice());
+        let modified = Sarc::new(writer.to_binary()).unwrap();
+        let diff = sarc.diff(&modified);
+        assert!(!diff.is_empty());
+        assert_eq!(diff, SarcDiff {
+            added:    ["Actor/Physics/New.bphysics".to_owned()].into(),
+            removed:  ["Actor/ModelList/ActorObserverTag.bmodellist".to_owned()].into(),
+            modified: ["Actor/AIProgram/ActorObserverByActorTagTag.baiprog".to_owned()].into(),
+        });
+        let reverse = modified.diff(&sarc);
+        assert_eq!(reverse.added, diff.removed);
+        assert_eq!(reverse.removed, diff.added);
+        assert_eq!(reverse.modified, diff.modified);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_extract_to_dir() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let dir = std::env::temp_dir().join("roead_par_extract_to_dir");
+        let _ = std::fs::remove_dir_all(&dir);
+        sarc.par_extract_to_dir(&dir).unwrap();
+        for file in sarc.files() {
+            assert_eq!(read(dir.join(file.unwrap_name())).unwrap(), file.data);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_names() {
+        for file in ["Dungeon119.pack", "test.sarc"] {
+            let data = read(std::path::Path::new("test/sarc").join(file)).unwrap();
+            let sarc = Sarc::new(&data).unwrap();
+            assert!(sarc.file_names().eq(sarc.files().map(|file| file.name)));
+        }
+    }
+
+    #[test]
+    fn diff_directory() {
+        let data = read("test/sarc/ActorObserverByActorTagTag.sarc").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let dir = std::env::temp_dir().join("roead_diff_directory");
+        let _ = std::fs::remove_dir_all(&dir);
+        sarc.extract_to_dir(&dir).unwrap();
+        assert!(sarc.diff_directory(&dir).unwrap().is_empty());
+
+        let changed = "Actor/ModelList/ActorObserverTag.bmodellist";
+        let mut file = read(dir.join(changed)).unwrap();
+        file.push(0);
+        std::fs::write(dir.join(changed), file).unwrap();
+        let diff = sarc.diff_directory(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(diff, SarcDiff {
+            modified: [changed.to_owned()].into(),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn size_breakdown() {
+        let data = read("test/sarc/test.sarc").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let breakdown = sarc.size_breakdown();
+        assert_eq!(breakdown.header, 0x14);
+        assert_eq!(breakdown.sfat, 0x0C + 0x10 * sarc.len());
+        assert_eq!(
+            breakdown.data,
+            sarc.files().map(|f| f.data().len()).sum::<usize>()
+        );
+        assert_eq!(breakdown.total(), data.len());
+
+        // FAT entries are not validated by `Sarc::new`, so out of range data
+        // offsets must not cause an overflow.
+        let mut data = data;
+        data[0x28..0x30].fill(0xFF);
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.size_breakdown().total(), data.len());
+    }
+
+    #[test]
+    fn iter_parsed() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let parsed = sarc.iter_parsed().collect::<Vec<_>>();
+        assert_eq!(parsed.len(), sarc.len());
+        for (name, file) in parsed {
+            let name = name.unwrap();
+            if name.ends_with(".smubin") {
+                #[cfg(all(any(feature = "yaz0", feature = "yaz0-rust"), feature = "byml"))]
+                assert!(matches!(file, ParsedFile::Byml(_)), "{name} should be BYML");
+                #[cfg(not(all(any(feature = "yaz0", feature = "yaz0-rust"), feature = "byml")))]
+                assert!(matches!(file, ParsedFile::Raw(_)), "{name} should be raw");
+            } else if name.ends_with(".shknm2") {
+                assert!(matches!(file, ParsedFile::Raw(_)), "{name} should be raw");
+            }
+        }
+    }
+
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    #[test]
+    fn iter_parsed_decompresses_once() {
+        use crate::yaz0::DECOMPRESS_CALLS;
+
+        let data = crate::yaz0::compress(read("test/sarc/Dungeon119.pack").unwrap());
+        DECOMPRESS_CALLS.with(|calls| calls.set(0));
+        let sarc = Sarc::new(data.as_ref()).unwrap();
+        assert_eq!(DECOMPRESS_CALLS.with(|calls| calls.get()), 1);
+
+        // Each compressed member is decompressed exactly once, and nested
+        // archives are passed on already decompressed.
+        let mut expected = 1 + sarc.compressed_file_count();
+        let mut nested = 0;
+        for (_, file) in sarc.iter_parsed() {
+            if let ParsedFile::Sarc(data) = file {
+                let inner = Sarc::new(&data[..]).unwrap();
+                expected += inner.compressed_file_count();
+                inner.iter_parsed().for_each(drop);
+                nested += 1;
+            }
+        }
+        assert_eq!(nested, 1);
+        assert_eq!(DECOMPRESS_CALLS.with(|calls| calls.get()), expected);
+    }
+
+    #[test]
+    fn new_validated() {
+        let mut data = read("test/sarc/test.sarc").unwrap();
+        Sarc::new_validated(data.as_slice()).unwrap();
+        // Point the data end of the second file far past the end of the archive
+        data[0x30 + 0xC..0x30 + 0x10].copy_from_slice(&0x7FFF_FFFFu32.to_be_bytes());
+        assert!(Sarc::new(data.as_slice()).is_ok());
+        match Sarc::new_validated(data.as_slice()) {
+            Err(Error::InvalidDataD(msg)) => assert!(msg.contains("index 1")),
+            other => panic!("Expected out of bounds error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn hash_multiplier() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.hash_multiplier(), 0x65);
+
+        // None of the fixtures use a non-standard multiplier, so patch one in
+        let mut data = read("test/sarc/test.sarc").unwrap();
+        data[0x1C..0x20].copy_from_slice(&0x1F_u32.to_be_bytes());
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.hash_multiplier(), 0x1F);
+    }
+
+    #[test]
+    fn names() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert!(sarc.has_names());
+        assert!(sarc.all_named());
+
+        // Clear the name table offset of each file entry
+        let mut data = read("test/sarc/test.sarc").unwrap();
+        data[0x24..0x28].fill(0);
+        let sarc = Sarc::new(&data).unwrap();
+        assert!(sarc.has_names());
+        assert!(!sarc.all_named());
+        for i in 1..sarc.len() {
+            data[0x24 + 0x10 * i..0x28 + 0x10 * i].fill(0);
+        }
+        let sarc = Sarc::new(&data).unwrap();
+        assert!(!sarc.has_names());
+        assert!(!sarc.all_named());
+        assert!(sarc.files().all(|file| file.name.is_none()));
+    }
+
+    #[test]
+    fn files_name_sorted() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let names = sarc
+            .files_name_sorted()
+            .into_iter()
+            .map(|file| file.name.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [
+            "Actor/Pack/DgnMrgPrt_Dungeon119.sbactorpack",
+            "Map/CDungeon/Dungeon119/Dungeon119_Clustering.sblwp",
+            "Map/CDungeon/Dungeon119/Dungeon119_Dynamic.smubin",
+            "Map/CDungeon/Dungeon119/Dungeon119_Static.smubin",
+            "Map/CDungeon/Dungeon119/Dungeon119_TeraTree.sblwp",
+            "Map/DungeonData/CDungeon/Dungeon119.bdgnenv",
+            "Model/DgnMrgPrt_Dungeon119.Tex2.sbfres",
+            "Model/DgnMrgPrt_Dungeon119.sbfres",
+            "NavMesh/CDungeon/Dungeon119/Dungeon119.shknm2",
+            "Physics/StaticCompound/CDungeon/Dungeon119.shksc",
+        ]);
+
+        let mut data = read("test/sarc/test.sarc").unwrap();
+        data[0x24..0x28].fill(0);
+        let sarc = Sarc::new(&data).unwrap();
+        let files = sarc.files_name_sorted();
+        assert_eq!(files.len(), sarc.len());
+        assert!(files.last().unwrap().name.is_none());
+        assert!(
+            files[..files.len() - 1]
+                .windows(2)
+                .all(|pair| pair[0].name.unwrap() <= pair[1].name.unwrap())
+        );
+    }
+
+    #[test]
+    fn compressed_files() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.len(), 10);
+        assert_eq!(sarc.compressed_file_count(), 9);
+        assert!(sarc.compressed_files().all(|file| file.is_compressed()));
+        let data = read("test/sarc/test.sarc").unwrap();
+        assert_eq!(Sarc::new(&data).unwrap().compressed_file_count(), 0);
+    }
+
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    #[test]
+    fn total_decompressed_size() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let expected: usize = sarc
+            .files()
+            .map(|file| {
+                if file.is_compressed() {
+                    file.decompressed_data().unwrap().len()
+                } else {
+                    file.data.len()
+                }
+            })
+            .sum();
+        assert_eq!(sarc.total_decompressed_size().unwrap(), expected);
+    }
+
+    #[test]
+    fn file_alignments() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let min_alignment = sarc.guess_min_alignment();
+        let alignments = sarc.file_alignments();
+        assert_eq!(alignments.len(), sarc.len());
+        for (name, alignment) in alignments {
+            assert!(name.is_some());
+            assert!(alignment.is_power_of_two());
+            assert!(alignment >= min_alignment);
+        }
+    }
+
+    #[test]
+    fn new_from_decompressed() {
+        let data = read("test/sarc/test.sarc").unwrap();
+        assert_eq!(
+            Sarc::new_from_decompressed(&data).unwrap(),
+            Sarc::new(&data).unwrap()
+        );
+        // Compressed data is never decompressed, so it can't be parsed
+        let data = read("test/yaz0/Demo344_1.sbeventpack").unwrap();
+        assert!(Sarc::new_from_decompressed(&data).is_err());
+    }
+
+    #[cfg(feature = "globset")]
+    #[test]
+    fn files_glob() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let mut names = sarc
+            .files_glob("Map/**/*.smubin")
+            .unwrap()
+            .filter_map(|file| file.name)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, [
+            "Map/CDungeon/Dungeon119/Dungeon119_Dynamic.smubin",
+            "Map/CDungeon/Dungeon119/Dungeon119_Static.smubin",
+        ]);
+        assert_eq!(sarc.files_glob("Model/*.sbfres").unwrap().count(), 2);
+        assert_eq!(sarc.files_glob("*.sbfres").unwrap().count(), 0);
+        assert!(matches!(
+            sarc.files_glob("Map/[").map(|files| files.count()),
+            Err(Error::InvalidGlob(_))
+        ));
+    }
 }
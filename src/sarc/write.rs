@@ -1,8 +1,9 @@
 use std::{
-    borrow::Borrow,
+    borrow::{Borrow, Cow},
     hash::Hash,
     io::{Cursor, Seek, SeekFrom},
     ops::Deref,
+    path::Path,
     sync::LazyLock,
 };
 
@@ -13,7 +14,7 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Deserialize;
 
 use super::*;
-use crate::{Endian, Result};
+use crate::{Endian, Error, Result};
 
 static FACTORY_INFO: &str = include_str!("../../data/botw_resource_factory_info.tsv");
 static AGLENV_INFO: &str = include_str!("../../data/aglenv_file_info.json");
@@ -83,31 +84,42 @@ pub struct SarcWriter {
     pub endian: Endian,
     legacy: bool,
     hash_multiplier: u32,
+    reserved: u16,
     min_alignment: usize,
     alignment_map: FxHashMap<String, usize>,
     brw_endian: binrw::Endian,
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    compress_extensions: FxHashSet<String>,
     /// Files to be written.
     pub files: IndexMap<String, Vec<u8>>,
 }
 
 impl std::fmt::Debug for SarcWriter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("SarcWriter")
-            .field("endian", &self.endian)
+        let mut f = f.debug_struct("SarcWriter");
+        f.field("endian", &self.endian)
             .field("legacy", &self.legacy)
             .field("hash_multiplier", &self.hash_multiplier)
+            .field("reserved", &self.reserved)
             .field("min_alignment", &self.min_alignment)
-            .field("alignment_map", &self.alignment_map)
-            .field("files", &self.files.keys().collect::<Vec<_>>())
+            .field("alignment_map", &self.alignment_map);
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        f.field("compress_extensions", &self.compress_extensions);
+        f.field("files", &self.files.keys().collect::<Vec<_>>())
             .finish()
     }
 }
 
 impl PartialEq for SarcWriter {
     fn eq(&self, other: &Self) -> bool {
+        #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+        if self.compress_extensions != other.compress_extensions {
+            return false;
+        }
         self.endian == other.endian
             && self.legacy == other.legacy
             && self.hash_multiplier == other.hash_multiplier
+            && self.reserved == other.reserved
             && self.min_alignment == other.min_alignment
             && self.alignment_map == other.alignment_map
             && self.files == other.files
@@ -123,7 +135,10 @@ impl SarcWriter {
             endian,
             legacy: false,
             hash_multiplier: HASH_MULTIPLIER,
+            reserved: 0,
             alignment_map: FxHashMap::default(),
+            #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+            compress_extensions: FxHashSet::default(),
             files: IndexMap::new(),
             brw_endian: match endian {
                 Endian::Big => binrw::Endian::Big,
@@ -133,18 +148,40 @@ impl SarcWriter {
         }
     }
 
+    /// A simple SARC archive writer, with space reserved for at least
+    /// `capacity` files
+    pub fn with_capacity(endian: Endian, capacity: usize) -> SarcWriter {
+        let mut writer = Self::new(endian);
+        writer.files.reserve(capacity);
+        writer
+    }
+
     /// Creates a new SARC writer by taking attributes and files
     /// from an existing SARC reader
     pub fn from_sarc(sarc: &Sarc) -> SarcWriter {
+        Self::from_sarc_filtered(sarc, |_| true)
+    }
+
+    /// Creates a new SARC writer like [`SarcWriter::from_sarc`], but only
+    /// copies the files whose names satisfy the given predicate. Files without
+    /// names are always dropped, as they cannot be written back.
+    pub fn from_sarc_filtered<F: Fn(&str) -> bool>(sarc: &Sarc, keep: F) -> SarcWriter {
         let endian = sarc.endian();
         SarcWriter {
             endian,
             legacy: false,
             hash_multiplier: HASH_MULTIPLIER,
+            reserved: sarc.reserved(),
             alignment_map: FxHashMap::default(),
+            #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+            compress_extensions: FxHashSet::default(),
             files: sarc
                 .files()
-                .filter_map(|f| f.name.map(|name| (name.to_string(), f.data.to_vec())))
+                .filter_map(|f| {
+                    f.name
+                        .filter(|name| keep(name))
+                        .map(|name| (name.to_string(), f.data.to_vec()))
+                })
                 .collect(),
             brw_endian: match endian {
                 Endian::Big => binrw::Endian::Big,
@@ -154,6 +191,39 @@ impl SarcWriter {
         }
     }
 
+    /// Creates a new SARC writer from the files in a directory tree. Each
+    /// file is named by its path relative to `dir`, using forward slashes as
+    /// separators.
+    pub fn from_directory<P: AsRef<Path>>(dir: P, endian: Endian) -> Result<SarcWriter> {
+        let dir = dir.as_ref();
+        let mut writer = SarcWriter::new(endian);
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(current) = dirs.pop() {
+            for entry in std::fs::read_dir(current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let name = path
+                    .strip_prefix(dir)
+                    .expect("Walked path should be inside the directory")
+                    .components()
+                    .map(|c| {
+                        c.as_os_str()
+                            .to_str()
+                            .ok_or(Error::InvalidData("File name is not valid UTF-8"))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .join("/");
+                let data = std::fs::read(&path)?;
+                writer.files.insert(name, data);
+            }
+        }
+        writer.files.sort_unstable_keys();
+        Ok(writer)
+    }
+
     /// Write a SARC archive to an in-memory buffer using the specified
     /// endianness. Default alignment requirements may be automatically
     /// added.
@@ -172,6 +242,30 @@ impl SarcWriter {
         buf
     }
 
+    /// Write a SARC archive to an in-memory buffer and compress it with yaz0
+    /// at the given compression level (6 to 9; 6 is fastest and 9 is
+    /// slowest). Default alignment requirements may be automatically added.
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    pub fn to_binary_compressed(&mut self, level: u8) -> Result<crate::Bytes> {
+        crate::yaz0::validate_level(level)?;
+        Ok(crate::yaz0::compress_with_options(
+            self.to_binary(),
+            crate::yaz0::CompressOptions {
+                compression_level: level,
+                ..Default::default()
+            },
+        ))
+    }
+
+    /// Write a yaz0 compressed SARC archive to a writer, as with
+    /// [`SarcWriter::to_binary_compressed`]. Unlike [`SarcWriter::write`],
+    /// the writer does not need to support seeking.
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    pub fn write_compressed<W: Write>(&mut self, writer: &mut W, level: u8) -> Result<()> {
+        writer.write_all(&self.to_binary_compressed(level)?)?;
+        Ok(())
+    }
+
     /// Write a SARC archive to a Write + Seek writer using the specified
     /// endianness. Default alignment requirements may be automatically
     /// added.
@@ -184,22 +278,32 @@ impl SarcWriter {
         }
         .write_options(writer, self.brw_endian, ())?;
 
+        // File data is written in the same order as the entries, so sorting by
+        // name as well keeps the output independent of insertion order even if
+        // two names have the same hash.
         self.files.sort_unstable_by(|ka, _, kb, _| {
-            hash_name(HASH_MULTIPLIER, ka).cmp(&hash_name(HASH_MULTIPLIER, kb))
+            sarc_hash_name(HASH_MULTIPLIER, ka)
+                .cmp(&sarc_hash_name(HASH_MULTIPLIER, kb))
+                .then_with(|| ka.cmp(kb))
         });
         self.add_default_alignments();
-        let mut alignments: Vec<usize> = Vec::with_capacity(self.files.len());
+        let files = self
+            .files
+            .iter()
+            .map(|(name, data)| (name, self.file_data_for_writing(name, data)))
+            .collect::<Vec<_>>();
+        let mut alignments: Vec<usize> = Vec::with_capacity(files.len());
 
         {
             let mut rel_string_offset = 0;
             let mut rel_data_offset = 0;
-            for (name, data) in self.files.iter() {
+            for (name, data) in files.iter() {
                 let alignment = self.get_alignment_for_file(name, data);
                 alignments.push(alignment);
 
                 let offset = align(rel_data_offset, alignment);
                 ResFatEntry {
-                    name_hash: hash_name(self.hash_multiplier, name.as_ref()),
+                    name_hash: sarc_hash_name(self.hash_multiplier, name.as_ref()),
                     rel_name_opt_offset: 1 << 24 | (rel_string_offset / 4),
                     data_begin: offset as u32,
                     data_end: (offset + data.len()) as u32,
@@ -216,7 +320,7 @@ impl SarcWriter {
             reserved: 0,
         }
         .write_options(writer, self.brw_endian, ())?;
-        for (name, _) in self.files.iter() {
+        for (name, _) in files.iter() {
             name.as_bytes().write_options(writer, self.brw_endian, ())?;
             0u8.write_options(writer, self.brw_endian, ())?;
             let pos = writer.stream_position()? as usize;
@@ -229,10 +333,10 @@ impl SarcWriter {
         let pos = writer.stream_position()? as usize;
         writer.seek(SeekFrom::Start(align(pos, required_alignment) as u64))?;
         let data_offset_begin = writer.stream_position()? as u32;
-        for ((_, data), alignment) in self.files.iter().zip(alignments.iter()) {
+        for ((_, data), alignment) in files.iter().zip(alignments.iter()) {
             let pos = writer.stream_position()? as usize;
             writer.seek(SeekFrom::Start(align(pos, *alignment) as u64))?;
-            data.write(writer)?;
+            data.as_ref().write(writer)?;
         }
 
         let file_size = writer.stream_position()? as u32;
@@ -243,7 +347,7 @@ impl SarcWriter {
             file_size,
             data_offset: data_offset_begin,
             version: 0x0100,
-            reserved: 0,
+            reserved: self.reserved,
         }
         .write_options(writer, self.brw_endian, ())?;
         Ok(())
@@ -280,22 +384,47 @@ impl SarcWriter {
         self
     }
 
+    fn default_alignments(endian: Endian) -> impl Iterator<Item = (&'static str, usize)> {
+        get_agl_env_alignment_requirements()
+            .iter()
+            .map(|(ext, alignment)| (ext.as_str(), *alignment))
+            .chain([
+                ("ksky", 8),
+                ("bksky", 8),
+                ("gtx", 0x2000),
+                ("sharcb", 0x1000),
+                ("sharc", 0x1000),
+                ("baglmf", 0x80),
+                ("bffnt", match endian {
+                    Endian::Big => 0x2000,
+                    Endian::Little => 0x1000,
+                }),
+            ])
+    }
+
     fn add_default_alignments(&mut self) {
         // This is perfectly sound because all of these alignments are powers
         // of 2 and thus the calls cannot fail.
-        for (ext, alignment) in get_agl_env_alignment_requirements() {
-            self.add_alignment_requirement(ext.clone(), *alignment);
+        for (ext, alignment) in Self::default_alignments(self.endian) {
+            self.add_alignment_requirement(ext.to_owned(), alignment);
         }
-        self.add_alignment_requirement("ksky".to_owned(), 8);
-        self.add_alignment_requirement("bksky".to_owned(), 8);
-        self.add_alignment_requirement("gtx".to_owned(), 0x2000);
-        self.add_alignment_requirement("sharcb".to_owned(), 0x1000);
-        self.add_alignment_requirement("sharc".to_owned(), 0x1000);
-        self.add_alignment_requirement("baglmf".to_owned(), 0x80);
-        self.add_alignment_requirement("bffnt".to_owned(), match self.endian {
-            Endian::Big => 0x2000,
-            Endian::Little => 0x1000,
-        });
+    }
+
+    /// Get the alignment which a file with the given extension (e.g.
+    /// “bgparamlist”) would receive, taking into account the default and
+    /// custom alignment requirements as well as the minimum alignment.
+    ///
+    /// Requirements which depend on the file data, such as those of
+    /// Nintendo binary files or nested archives, are not included.
+    pub fn alignment_for_extension(&self, ext: &str) -> usize {
+        let ext = ext.trim_start_matches('.');
+        let requirement = Self::default_alignments(self.endian)
+            .filter(|(default_ext, _)| *default_ext == ext)
+            .last()
+            .map(|(_, alignment)| alignment)
+            .or_else(|| self.alignment_map.get(ext).copied())
+            .unwrap_or(1);
+        self.min_alignment.lcm(&requirement)
     }
 
     /// Set the minimum data alignment.
@@ -317,6 +446,37 @@ impl SarcWriter {
         self
     }
 
+    /// Compute the smallest alignment which satisfies the actual requirement
+    /// of every file in the archive (the LCM of the per-file requirements),
+    /// ignoring the configured minimum alignment. Passing the result to
+    /// [`SarcWriter::set_min_alignment`] produces the smallest correct
+    /// archive. Default alignment requirements may be automatically added.
+    pub fn minimal_alignment(&mut self) -> usize {
+        self.add_default_alignments();
+        self.files
+            .iter()
+            .map(|(name, data)| {
+                self.get_file_alignment_requirement(name, &self.file_data_for_writing(name, data))
+            })
+            .fold(1, |acc, alignment| acc.lcm(&alignment))
+    }
+
+    /// Compute the alignment which each file will be written with, including
+    /// the minimum alignment, e.g. to find out why a rebuilt archive does not
+    /// match the original byte for byte. Default alignment requirements may be
+    /// automatically added.
+    pub fn alignment_report(&mut self) -> Vec<(String, usize)> {
+        self.add_default_alignments();
+        self.files
+            .iter()
+            .map(|(name, data)| {
+                let alignment =
+                    self.get_alignment_for_file(name, &self.file_data_for_writing(name, data));
+                (name.clone(), alignment)
+            })
+            .collect()
+    }
+
     /// Set whether to use legacy mode (for games without a BOTW-style
     /// resource system) for addtional alignment restrictions
     #[inline]
@@ -333,6 +493,26 @@ impl SarcWriter {
         self
     }
 
+    /// Set the file extensions (without the dot, e.g. “sbfres”) of files
+    /// which should be yaz0 compressed when writing the archive. Files which
+    /// are already compressed are stored as-is.
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    pub fn set_compress_extensions(&mut self, exts: &[&str]) {
+        self.compress_extensions = exts
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_owned())
+            .collect();
+    }
+
+    /// Builder-style method to set the file extensions of files which should
+    /// be yaz0 compressed when writing the archive.
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    #[inline]
+    pub fn with_compress_extensions(mut self, exts: &[&str]) -> Self {
+        self.set_compress_extensions(exts);
+        self
+    }
+
     /// Set the endianness
     #[inline]
     pub fn set_endian(&mut self, endian: Endian) {
@@ -384,13 +564,38 @@ impl SarcWriter {
         }
     }
 
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    fn file_data_for_writing<'d>(&self, name: &str, data: &'d [u8]) -> Cow<'d, [u8]> {
+        let ext = match name.rfind('.') {
+            Some(idx) => &name[idx + 1..],
+            None => "",
+        };
+        if self.compress_extensions.contains(ext) && !data.starts_with(b"Yaz0") {
+            crate::yaz0::compress(data).into()
+        } else {
+            Cow::Borrowed(data)
+        }
+    }
+
+    #[cfg(not(any(feature = "yaz0", feature = "yaz0-rust")))]
+    #[inline(always)]
+    fn file_data_for_writing<'d>(&self, _name: &str, data: &'d [u8]) -> Cow<'d, [u8]> {
+        Cow::Borrowed(data)
+    }
+
+    #[inline]
     fn get_alignment_for_file(&self, name: impl AsRef<str>, data: &[u8]) -> usize {
+        self.min_alignment
+            .lcm(&self.get_file_alignment_requirement(name, data))
+    }
+
+    fn get_file_alignment_requirement(&self, name: impl AsRef<str>, data: &[u8]) -> usize {
         let name = name.as_ref();
         let ext = match name.rfind('.') {
             Some(idx) => &name[idx + 1..],
             None => "",
         };
-        let mut alignment = self.min_alignment;
+        let mut alignment = 1;
         if let Some(requirement) = self.alignment_map.get(ext) {
             alignment = alignment.lcm(requirement);
         }
@@ -470,9 +675,25 @@ impl From<&Sarc<'_>> for SarcWriter {
     }
 }
 
+/// Collects files into a little endian SARC writer.
+impl<N, D> FromIterator<(N, D)> for SarcWriter
+where
+    N: Into<String>,
+    D: Into<Vec<u8>>,
+{
+    fn from_iter<T: IntoIterator<Item = (N, D)>>(iter: T) -> Self {
+        let mut writer = Self::new(Endian::Little);
+        writer.add_files(iter);
+        writer
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::sarc::{Sarc, SarcWriter};
+    use crate::{
+        sarc::{Sarc, SarcWriter},
+        Endian,
+    };
 
     #[test]
     fn make_sarc() {
@@ -512,4 +733,174 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn reserved_roundtrip() {
+        let mut data = std::fs::read("test/sarc/test.sarc").unwrap();
+        assert_eq!(Sarc::new(&data).unwrap().reserved(), 0);
+        data[0x12..0x14].copy_from_slice(&0x1234_u16.to_be_bytes());
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.reserved(), 0x1234);
+        let new_data = SarcWriter::from_sarc(&sarc).to_binary();
+        assert_eq!(Sarc::new(&new_data).unwrap().reserved(), 0x1234);
+        assert_eq!(data, new_data);
+    }
+
+    #[test]
+    fn from_sarc_filtered() {
+        let data = std::fs::read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let mut sarc_writer =
+            SarcWriter::from_sarc_filtered(&sarc, |name| name.ends_with(".smubin"));
+        let new_data = sarc_writer.to_binary();
+        let new_sarc = Sarc::new(&new_data).unwrap();
+        let mut names: Vec<_> = new_sarc.files().filter_map(|f| f.name).collect();
+        names.sort_unstable();
+        assert_eq!(names, [
+            "Map/CDungeon/Dungeon119/Dungeon119_Dynamic.smubin",
+            "Map/CDungeon/Dungeon119/Dungeon119_Static.smubin",
+        ]);
+        for file in new_sarc.files() {
+            assert_eq!(file.data, sarc.get_data(file.name.unwrap()).unwrap());
+        }
+    }
+
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    #[test]
+    fn compress_extensions() {
+        let data = b"BFRES Some model data which will be compressed".repeat(16);
+        let mut sarc_writer = SarcWriter::new(crate::Endian::Little)
+            .with_compress_extensions(&["sbfres"])
+            .with_file("Model/Test.sbfres", data.clone())
+            .with_file("Model/Test.bfres", data.clone());
+        let new_data = sarc_writer.to_binary();
+        let sarc = Sarc::new(&new_data).unwrap();
+        let compressed = sarc.get("Model/Test.sbfres").unwrap();
+        assert!(compressed.is_compressed());
+        assert_eq!(compressed.decompressed_data().unwrap(), data);
+        let uncompressed = sarc.get("Model/Test.bfres").unwrap();
+        assert!(!uncompressed.is_compressed());
+        assert_eq!(uncompressed.data(), data.as_slice());
+    }
+
+    #[test]
+    fn minimal_alignment() {
+        let mut writer = SarcWriter::new(Endian::Big).with_min_alignment(0x100);
+        assert_eq!(writer.minimal_alignment(), 1);
+        writer.add_file("Plain.txt", b"text".as_slice());
+        assert_eq!(writer.minimal_alignment(), 1);
+        writer.add_file("Sky.ksky", b"sky".as_slice());
+        assert_eq!(writer.minimal_alignment(), 8);
+        writer.add_file("Model.baglmf", b"model".as_slice());
+        assert_eq!(writer.minimal_alignment(), 0x80);
+        writer.add_alignment_requirement("bin".into(), 0x20);
+        writer.add_file("Data.bin", b"data".as_slice());
+        assert_eq!(writer.minimal_alignment(), 0x80);
+        writer.add_alignment_requirement("bin".into(), 0x200);
+        assert_eq!(writer.minimal_alignment(), 0x200);
+    }
+
+    #[test]
+    fn from_directory() {
+        let data = std::fs::read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let dir = std::env::temp_dir().join("roead_from_directory");
+        let _ = std::fs::remove_dir_all(&dir);
+        sarc.extract_to_dir(&dir).unwrap();
+        let mut writer = SarcWriter::from_directory(&dir, Endian::Big).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(writer.files.len(), sarc.len());
+        assert!(
+            writer
+                .files
+                .contains_key("NavMesh/CDungeon/Dungeon119/Dungeon119.shknm2")
+        );
+        assert_eq!(writer.to_binary(), SarcWriter::from_sarc(&sarc).to_binary());
+    }
+
+    #[cfg(any(feature = "yaz0", feature = "yaz0-rust"))]
+    #[test]
+    fn to_binary_compressed() {
+        let data = std::fs::read("test/sarc/ActorObserverByActorTagTag.sarc").unwrap();
+        let mut writer = SarcWriter::from_sarc(&Sarc::new(&data).unwrap());
+        let compressed = writer.to_binary_compressed(9).unwrap();
+        assert!(compressed.starts_with(b"Yaz0"));
+        assert_eq!(
+            crate::yaz0::decompress(&compressed).unwrap(),
+            writer.to_binary()
+        );
+        let mut buf = Vec::new();
+        writer.write_compressed(&mut buf, 9).unwrap();
+        assert_eq!(buf.as_slice(), compressed.as_ref());
+        assert!(writer.to_binary_compressed(5).is_err());
+        assert!(writer.write_compressed(&mut buf, 10).is_err());
+    }
+
+    #[test]
+    fn insertion_order() {
+        let data = std::fs::read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let mut writer = SarcWriter::from_sarc(&sarc);
+        let mut reversed = SarcWriter::from_sarc(&sarc);
+        reversed.files.reverse();
+        // Names with the same hash (0xD0A50A87)
+        writer.add_file("zZsptBwC", b"first".as_slice());
+        writer.add_file("gebzOSaH", b"second".as_slice());
+        reversed.add_file("gebzOSaH", b"second".as_slice());
+        reversed.add_file("zZsptBwC", b"first".as_slice());
+        assert_eq!(writer.to_binary(), reversed.to_binary());
+    }
+
+    #[test]
+    fn from_iter() {
+        let writer = SarcWriter::with_capacity(Endian::Big, 100);
+        assert!(writer.files.capacity() >= 100);
+        assert!(writer.files.is_empty());
+
+        let files = [
+            ("A/File.txt".to_owned(), b"test".to_vec()),
+            ("B/File.txt".to_owned(), b"another test".to_vec()),
+        ];
+        let mut writer: SarcWriter = files.clone().into_iter().collect();
+        assert_eq!(writer.endian, Endian::Little);
+        let data = writer.to_binary();
+        let sarc = Sarc::new(&data).unwrap();
+        for (name, data) in files {
+            assert_eq!(sarc.get_data(&name).unwrap(), data.as_slice());
+        }
+    }
+
+    #[test]
+    fn alignment_report() {
+        let mut writer = SarcWriter::new(Endian::Big)
+            .with_file("Plain.txt", b"text".as_slice())
+            .with_file("Model.baglmf", b"model".as_slice())
+            .with_file("Font.bffnt", b"font".as_slice());
+        assert_eq!(writer.alignment_report(), [
+            ("Plain.txt".to_owned(), 4),
+            ("Model.baglmf".to_owned(), 0x80),
+            ("Font.bffnt".to_owned(), 0x2000),
+        ]);
+        let data = writer.to_binary();
+        let sarc = Sarc::new(&data).unwrap();
+        for (name, alignment) in writer.alignment_report() {
+            let offset = sarc.get_data(&name).unwrap().as_ptr() as usize - data.as_ptr() as usize;
+            assert_eq!(offset % alignment, 0);
+        }
+    }
+
+    #[test]
+    fn alignment_for_extension() {
+        let mut writer = SarcWriter::new(Endian::Big);
+        assert_eq!(writer.alignment_for_extension(".bffnt"), 0x2000);
+        assert_eq!(writer.alignment_for_extension("baglmf"), 0x80);
+        assert_eq!(writer.alignment_for_extension("unknown"), 4);
+        writer.add_alignment_requirement("unknown".into(), 0x40);
+        assert_eq!(writer.alignment_for_extension("unknown"), 0x40);
+        writer.set_endian(Endian::Little);
+        writer.set_min_alignment(0x100);
+        assert_eq!(writer.alignment_for_extension("bffnt"), 0x1000);
+        assert_eq!(writer.alignment_for_extension("unknown"), 0x100);
+        assert_eq!(writer.alignment_for_extension(""), 0x100);
+    }
 }
@@ -83,6 +83,45 @@ impl<const N: usize> FixedSafeString<N> {
             self.len = new_len;
         }
     }
+
+    /// Copy the string into a `FixedSafeString` with a different capacity.
+    /// Unlike the conversions from `&str`, this does not truncate: if the
+    /// contents do not fit in `M` bytes, an error is returned instead.
+    pub fn resize<const M: usize>(&self) -> crate::Result<FixedSafeString<M>> {
+        if self.len > M {
+            return Err(crate::Error::InvalidDataD(format!(
+                "String of length {} does not fit in FixedSafeString<{M}>",
+                self.len
+            )));
+        }
+        let mut data = [0; M];
+        data[..self.len].copy_from_slice(&self.data[..self.len]);
+        Ok(FixedSafeString {
+            data,
+            len: self.len,
+        })
+    }
+
+    /// Check that the stored string is valid UTF-8 without any null bytes,
+    /// and that it is null-terminated within `N` bytes (i.e. it is at most
+    /// `N - 1` bytes long). Strings parsed from binary data are not checked
+    /// on construction, so this should be used when reading untrusted data.
+    pub fn validate(&self) -> crate::Result<()> {
+        let bytes = &self.data[..self.len];
+        if self.len >= N {
+            return Err(crate::Error::InvalidDataD(format!(
+                "String is not null-terminated within {N} bytes"
+            )));
+        }
+        if let Some(pos) = bytes.iter().position(|b| *b == 0) {
+            return Err(crate::Error::InvalidDataD(format!(
+                "String contains a null byte at offset {pos}"
+            )));
+        }
+        std::str::from_utf8(bytes)
+            .map_err(|e| crate::Error::InvalidDataD(format!("String is not valid UTF-8: {e}")))?;
+        Ok(())
+    }
 }
 
 impl<const N: usize> std::ops::Deref for FixedSafeString<N> {
@@ -181,6 +220,7 @@ impl<const N: usize> binrw::BinRead for FixedSafeString<N> {
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "binrw", binrw::binrw)]
+#[repr(C)]
 pub struct Vector2f {
     pub x: f32,
     pub y: f32,
@@ -204,6 +244,7 @@ impl std::hash::Hash for Vector2f {
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "binrw", binrw::binrw)]
+#[repr(C)]
 pub struct Vector3f {
     pub x: f32,
     pub y: f32,
@@ -234,6 +275,7 @@ impl std::hash::Hash for Vector3f {
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "binrw", binrw::binrw)]
+#[repr(C)]
 pub struct Vector4f {
     pub x: f32,
     pub y: f32,
@@ -268,6 +310,7 @@ impl std::hash::Hash for Vector4f {
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "binrw", binrw::binrw)]
+#[repr(C)]
 pub struct Quat {
     pub a: f32,
     pub b: f32,
@@ -302,6 +345,7 @@ impl std::hash::Hash for Quat {
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "binrw", binrw::binrw)]
+#[repr(C)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -342,6 +386,158 @@ pub struct Curve {
     pub floats: [f32; 30],
 }
 
+/// Interpolation mode of a [`Curve`] (`sead::hostio::CurveType`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CurveMode {
+    Linear,
+    Hermit,
+    Step,
+    Sin,
+    Cos,
+    SinPow2,
+    Linear2D,
+    Hermit2D,
+    Step2D,
+    NonuniformSpline,
+    Hermit2DSmooth,
+    /// Unrecognized curve type value.
+    Unknown(u32),
+}
+
+impl From<u32> for CurveMode {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => CurveMode::Linear,
+            1 => CurveMode::Hermit,
+            2 => CurveMode::Step,
+            3 => CurveMode::Sin,
+            4 => CurveMode::Cos,
+            5 => CurveMode::SinPow2,
+            6 => CurveMode::Linear2D,
+            7 => CurveMode::Hermit2D,
+            8 => CurveMode::Step2D,
+            9 => CurveMode::NonuniformSpline,
+            10 => CurveMode::Hermit2DSmooth,
+            _ => CurveMode::Unknown(value),
+        }
+    }
+}
+
+impl Curve {
+    /// The control point values actually used by the curve (the first `a`
+    /// floats).
+    pub fn control_points(&self) -> &[f32] {
+        &self.floats[..(self.a as usize).min(self.floats.len())]
+    }
+
+    /// The interpolation mode of the curve (stored in `b`).
+    pub fn mode(&self) -> CurveMode {
+        self.b.into()
+    }
+
+    /// Sample the curve at `t`, which normally ranges from 0 to 1.
+    ///
+    /// The control points are interpreted according to the curve mode:
+    ///
+    /// * [`CurveMode::Linear`] and [`CurveMode::Step`]: values evenly spaced over `0..=1`.
+    /// * [`CurveMode::Hermit`]: evenly spaced `(value, slope)` pairs.
+    /// * [`CurveMode::Linear2D`] and [`CurveMode::Step2D`]: `(x, y)` pairs sorted by `x`.
+    /// * [`CurveMode::Hermit2D`]: `(x, y, slope)` triples sorted by `x`.
+    ///
+    /// Samples outside the range of the control points are clamped to the
+    /// first or last value. An empty curve evaluates to 0. The other modes
+    /// are not supported and return `None`.
+    pub fn eval(&self, t: f32) -> Option<f32> {
+        #[inline]
+        fn hermite(p0: f32, m0: f32, p1: f32, m1: f32, s: f32) -> f32 {
+            let s2 = s * s;
+            let s3 = s2 * s;
+            (2.0 * s3 - 3.0 * s2 + 1.0) * p0
+                + (s3 - 2.0 * s2 + s) * m0
+                + (-2.0 * s3 + 3.0 * s2) * p1
+                + (s3 - s2) * m1
+        }
+
+        /// Find the segment of sorted 2D points containing `t` and
+        /// interpolate within it, clamping outside the points.
+        fn eval_2d(
+            points: &[f32],
+            stride: usize,
+            t: f32,
+            f: impl Fn(&[f32], &[f32]) -> f32,
+        ) -> f32 {
+            let count = points.len() / stride;
+            let point = |i: usize| &points[i * stride..(i + 1) * stride];
+            if count == 0 {
+                return 0.0;
+            }
+            if t <= point(0)[0] {
+                return point(0)[1];
+            }
+            (1..count)
+                .find(|&i| t < point(i)[0])
+                .map(|i| f(point(i - 1), point(i)))
+                .unwrap_or(point(count - 1)[1])
+        }
+
+        let points = self.control_points();
+        Some(match self.mode() {
+            CurveMode::Sin
+            | CurveMode::Cos
+            | CurveMode::SinPow2
+            | CurveMode::NonuniformSpline
+            | CurveMode::Hermit2DSmooth
+            | CurveMode::Unknown(_) => return None,
+            _ if points.is_empty() => 0.0,
+            CurveMode::Linear => {
+                let x = t.clamp(0.0, 1.0) * (points.len() - 1) as f32;
+                let i = (x as usize).min(points.len() - 1);
+                match points.get(i + 1) {
+                    Some(next) => points[i] + (next - points[i]) * (x - i as f32),
+                    None => points[i],
+                }
+            }
+            CurveMode::Hermit => {
+                // Evenly spaced (value, slope) pairs
+                let count = points.len() / 2;
+                if count < 2 {
+                    return Some(if count == 1 { points[0] } else { 0.0 });
+                }
+                let width = 1.0 / (count - 1) as f32;
+                let x = t.clamp(0.0, 1.0) * (count - 1) as f32;
+                let i = x as usize;
+                if i >= count - 1 {
+                    return Some(points[2 * (count - 1)]);
+                }
+                let (p0, p1) = (&points[2 * i..2 * i + 2], &points[2 * i + 2..2 * i + 4]);
+                hermite(p0[0], p0[1] * width, p1[0], p1[1] * width, x - i as f32)
+            }
+            CurveMode::Step => {
+                let i = (t.clamp(0.0, 1.0) * points.len() as f32) as usize;
+                points[i.min(points.len() - 1)]
+            }
+            CurveMode::Linear2D => {
+                eval_2d(points, 2, t, |p0, p1| {
+                    p0[1] + (p1[1] - p0[1]) * (t - p0[0]) / (p1[0] - p0[0])
+                })
+            }
+            CurveMode::Hermit2D => {
+                eval_2d(points, 3, t, |p0, p1| {
+                    let width = p1[0] - p0[0];
+                    hermite(
+                        p0[1],
+                        p0[2] * width,
+                        p1[1],
+                        p1[2] * width,
+                        (t - p0[0]) / width,
+                    )
+                })
+            }
+            CurveMode::Step2D => eval_2d(points, 2, t, |p0, _| p0[1]),
+        })
+    }
+}
+
 #[cfg(feature = "almost")]
 impl PartialEq for Curve {
     fn eq(&self, other: &Self) -> bool {
@@ -365,3 +561,90 @@ impl std::hash::Hash for Curve {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_fixed_string() {
+        let s32 = FixedSafeString::<32>::from("Weapon_Sword_001");
+        let s64 = s32.resize::<64>().unwrap();
+        assert_eq!(s64.as_str(), "Weapon_Sword_001");
+        assert_eq!(s64.resize::<16>().unwrap().as_str(), "Weapon_Sword_001");
+        assert!(matches!(
+            s64.resize::<8>(),
+            Err(crate::Error::InvalidDataD(_))
+        ));
+    }
+
+    #[test]
+    fn validate_fixed_string() {
+        assert!(
+            FixedSafeString::<32>::from("Weapon_Sword_001")
+                .validate()
+                .is_ok()
+        );
+        assert!(FixedSafeString::<32>::default().validate().is_ok());
+        assert!(FixedSafeString::<4>::from("Sword").validate().is_err());
+        let mut invalid = FixedSafeString::<8> {
+            data: *b"Sw\xFFrd\0\0\0",
+            len:  5,
+        };
+        assert!(invalid.validate().is_err());
+        invalid.data[2] = 0;
+        assert!(invalid.validate().is_err());
+        invalid.data[2] = b'o';
+        assert!(invalid.validate().is_ok());
+    }
+
+    fn curve(mode: u32, points: &[f32]) -> Curve {
+        let mut floats = [0.0; 30];
+        floats[..points.len()].copy_from_slice(points);
+        Curve {
+            a: points.len() as u32,
+            b: mode,
+            floats,
+        }
+    }
+
+    #[test]
+    fn eval_curve() {
+        // Linear 2D curve from BOTW's common.bagllmap
+        let linear_2d = curve(6, &[
+            0.0, 0.0, 0.55, 0.0, 0.7, 0.7, 0.9, 0.7, 0.95, 1.0, 1.0, 1.0,
+        ]);
+        assert_eq!(linear_2d.eval(0.0), Some(0.0));
+        assert_eq!(linear_2d.eval(0.3), Some(0.0));
+        assert!((linear_2d.eval(0.625).unwrap() - 0.35).abs() < 1e-5);
+        assert_eq!(linear_2d.eval(0.8), Some(0.7));
+        assert_eq!(linear_2d.eval(1.0), Some(1.0));
+        assert_eq!(linear_2d.eval(2.0), Some(1.0));
+
+        // Hermit 2D curves from BOTW's common.bagllmap
+        let flat = curve(7, &[0.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(flat.eval(0.0), Some(1.0));
+        assert_eq!(flat.eval(0.5), Some(1.0));
+        let step = curve(7, &[0.0, 0.0, 0.0, 0.55, 0.0, 0.0, 0.55, 1.0, 0.0]);
+        assert_eq!(step.eval(0.5), Some(0.0));
+        assert_eq!(step.eval(0.55), Some(1.0));
+        assert_eq!(step.eval(0.9), Some(1.0));
+        let smooth = curve(7, &[0.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(smooth.eval(0.5), Some(0.5));
+        assert_eq!(smooth.eval(0.25), Some(0.15625));
+
+        let linear = curve(0, &[0.0, 1.0, 3.0]);
+        assert_eq!(linear.eval(0.25), Some(0.5));
+        assert_eq!(linear.eval(0.75), Some(2.0));
+        assert_eq!(linear.eval(1.0), Some(3.0));
+        let hermit = curve(1, &[0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(hermit.eval(0.5), Some(0.5));
+        let step = curve(2, &[1.0, 2.0]);
+        assert_eq!(step.eval(0.25), Some(1.0));
+        assert_eq!(step.eval(0.5), Some(2.0));
+        assert_eq!(step.eval(1.0), Some(2.0));
+
+        assert_eq!(curve(0, &[]).eval(0.5), Some(0.0));
+        assert_eq!(curve(3, &[1.0, 1.0]).eval(0.5), None);
+    }
+}
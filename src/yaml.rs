@@ -1,3 +1,4 @@
+//! Shared helpers for the YAML representations of AAMP and BYML documents.
 use core::str;
 use std::sync::LazyLock;
 
@@ -67,7 +68,7 @@ fn parse_int(value: &str) -> Result<i128> {
                 &lexical::ParseIntegerOptions::default(),
             )
         })
-        .map_err(|_| Error::InvalidDataD(jstr!("Invalid integer: {value}")))
+        .map_err(|_| Error::InvalidDataD(jstr!("Could not parse number '{value}'")))
 }
 
 #[inline]
@@ -81,7 +82,7 @@ fn parse_float(value: &str) -> Result<f64> {
         Ok(f64::NAN)
     } else {
         lexical::parse(value.as_bytes())
-            .map_err(|_| Error::InvalidDataD(jstr!("Invalid float: {value}")))
+            .map_err(|_| Error::InvalidDataD(jstr!("Could not parse number '{value}'")))
     }
 }
 
@@ -175,3 +176,234 @@ macro_rules! format_hex {
     };
 }
 pub(crate) use format_hex;
+
+/// Tags used by the AAMP YAML format.
+const AAMP_TAGS: &[&str] = &[
+    "!io",
+    "!list",
+    "!obj",
+    "!vec2",
+    "!vec3",
+    "!vec4",
+    "!quat",
+    "!color",
+    "!curve",
+    "!buffer_int",
+    "!buffer_f32",
+    "!buffer_u32",
+    "!buffer_binary",
+    "!str32",
+    "!str64",
+    "!str256",
+    "!u",
+];
+
+#[inline]
+fn is_known_tag(tag: &str) -> bool {
+    AAMP_TAGS.contains(&tag)
+        || get_tag_based_type(tag).is_some()
+        || tag
+            .strip_prefix("!!")
+            .map(|name| get_tag_based_type(&jstr!("tag:yaml.org,2002:{name}")).is_some())
+            .unwrap_or(false)
+}
+
+fn collect_unknown_tags<'a, 't>(
+    node: &ryml::NodeRef<'a, 't, '_, &'t ryml::Tree<'a>>,
+    unknown: &mut Vec<String>,
+) -> Result<()> {
+    for tag in [
+        node.has_key_tag()?.then(|| node.key_tag()).transpose()?,
+        node.has_val_tag()?.then(|| node.val_tag()).transpose()?,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !is_known_tag(tag) && !unknown.iter().any(|t| t == tag) {
+            unknown.push(tag.into());
+        }
+    }
+    for child in node.iter()? {
+        collect_unknown_tags(&child, unknown)?;
+    }
+    Ok(())
+}
+
+/// Find the (1-based) numbers of the lines on which a tag is used.
+fn find_tag_lines(text: &str, tag: &str) -> Vec<usize> {
+    let is_boundary = |c: Option<char>| {
+        c.map(|c| c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}' | ':' | '-'))
+            .unwrap_or(true)
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            line.match_indices(tag).any(|(i, _)| {
+                is_boundary(line[..i].chars().next_back())
+                    && is_boundary(line[i + tag.len()..].chars().next())
+            })
+        })
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+/// Check that every tag in an AAMP YAML document is one roead understands,
+/// before parsing it with `ParameterIO::from_text`.
+///
+/// Unrecognized tags (like `!str` instead of `!str32`) are otherwise ignored
+/// by the parser, so a hand-edited value can silently end up with the wrong
+/// type. This returns an [`Error::InvalidDataD`] listing each unrecognized
+/// tag with the lines on which it is used.
+pub fn validate_tags(text: &str) -> Result<()> {
+    let tree = ryml::Tree::parse(text)?;
+    let mut unknown = Vec::new();
+    collect_unknown_tags(&tree.root_ref()?, &mut unknown)?;
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(Error::InvalidDataD(
+        unknown
+            .iter()
+            .map(|tag| {
+                let lines = find_tag_lines(text, tag);
+                match lines.as_slice() {
+                    [] => format!("Unrecognized YAML tag `{tag}`"),
+                    [line] => format!("Unrecognized YAML tag `{tag}` on line {line}"),
+                    lines => {
+                        format!(
+                            "Unrecognized YAML tag `{tag}` on lines {}",
+                            lines
+                                .iter()
+                                .map(|line| line.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; "),
+    ))
+}
+
+/// Output options for serializing documents to YAML text.
+///
+/// By default roead emits LF line endings with no byte order mark. Tools which
+/// need to match files authored on Windows can enable either option to avoid
+/// noisy diffs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YamlOptions {
+    /// Prepend a UTF-8 byte order mark to the output.
+    pub bom: bool,
+    /// Use CRLF line endings instead of LF.
+    pub crlf: bool,
+    /// Tag AAMP parameter objects with `!obj` and parameter lists with
+    /// `!list`. Some consumers don't expect these tags; the AAMP parser
+    /// accepts documents with or without them. Enabled by default; has no
+    /// effect on BYML documents.
+    pub aamp_struct_tags: bool,
+}
+
+impl Default for YamlOptions {
+    fn default() -> Self {
+        Self {
+            bom: false,
+            crlf: false,
+            aamp_struct_tags: true,
+        }
+    }
+}
+
+impl YamlOptions {
+    /// Apply the options to YAML text emitted with the default settings.
+    #[cfg(feature = "aamp")]
+    pub(crate) fn apply(&self, text: std::string::String) -> std::string::String {
+        let text = if self.crlf {
+            text.replace('\n', "\r\n")
+        } else {
+            text
+        };
+        if self.bom {
+            format!("\u{FEFF}{text}")
+        } else {
+            text
+        }
+    }
+}
+
+/// Check whether two AAMP or BYML YAML documents are semantically equal, i.e.
+/// whether they parse into equal values regardless of formatting, quoting
+/// style, or key order. This is mostly useful as a testing aid.
+#[cfg(all(feature = "aamp", feature = "byml"))]
+pub fn semantic_eq(a: &str, b: &str, format: crate::convert::Format) -> Result<bool> {
+    use crate::convert::Format;
+    match format {
+        Format::Aamp => {
+            Ok(crate::aamp::ParameterIO::from_text(a)? == crate::aamp::ParameterIO::from_text(b)?)
+        }
+        Format::Byml => Ok(crate::byml::Byml::from_text(a)? == crate::byml::Byml::from_text(b)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_tags() {
+        let text = std::fs::read_to_string("test/aamp/test.yml").unwrap();
+        super::validate_tags(&text).unwrap();
+
+        let text = "!io
+version: 0
+type: xml
+param_root: !list
+  objects:
+    TestContent: !obj
+      Str: !str Hello
+      Str32: !str32 Hello
+      Vec: !vector [1.0, 2.0]
+  lists: {}
+";
+        match super::validate_tags(text) {
+            Err(Error::InvalidDataD(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Unrecognized YAML tag `!str` on line 7; Unrecognized YAML tag `!vector` on \
+                     line 9"
+                )
+            }
+            other => panic!("Expected unrecognized tag error, got {other:?}"),
+        }
+    }
+
+    #[cfg(all(feature = "aamp", feature = "byml"))]
+    #[test]
+    fn semantic_eq() {
+        use crate::convert::Format;
+        let a = "!io
+version: 0
+type: xml
+param_root: !list
+  objects:
+    TestContent: !obj
+      Bool: true
+      F32: 1.5
+      Vec: !vec3 [1.0, 2.0, 3.0]
+  lists: {}
+";
+        let b = "!io
+type: xml
+version: 0
+param_root: !list {lists: {}, objects: {TestContent: !obj {
+  Vec: !vec3 [1.00, 2.0, 3.0], F32: 1.50, Bool: true}}}
+";
+        assert!(super::semantic_eq(a, b, Format::Aamp).unwrap());
+        let c = a.replace("1.5", "2.5");
+        assert!(!super::semantic_eq(a, &c, Format::Aamp).unwrap());
+
+        let a = "{Name: Test, Values: [1, 2.0, \"3\"]}";
+        let b = "Values:\n  - 1\n  - 2.0\n  - '3'\nName: 'Test'\n";
+        assert!(super::semantic_eq(a, b, Format::Byml).unwrap());
+    }
+}
@@ -1,10 +1,14 @@
 //! Bindings for the `oead::yaz0` module, which supports Yaz0 decompression and
 //! fast compression (using syaz0).
-use std::borrow::Cow;
+//!
+//! With the `yaz0-rust` feature, decompression and compression are instead
+//! implemented in pure Rust, so the module can be used without the C++
+//! toolchain which the `yaz0` feature requires.
+use std::{borrow::Cow, io::Write};
 
 use binrw::binrw;
 
-use crate::{Error, Result};
+use crate::{Bytes, Error, Result};
 
 /// The header of Yaz0 compressed data.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -26,9 +30,9 @@ pub fn get_header(data: impl AsRef<[u8]>) -> Option<Header> {
     binrw::BinRead::read(&mut std::io::Cursor::new(data.as_ref())).ok()
 }
 
-/// Decompress Yaz0 data to vector.
-pub fn decompress(data: impl AsRef<[u8]>) -> Result<Vec<u8>> {
-    let data = data.as_ref();
+/// Read the header of Yaz0 compressed data, failing if it is missing or does
+/// not have the Yaz0 magic.
+fn read_checked_header(data: &[u8]) -> Result<Header> {
     if data.len() < 0x16 {
         return Err(Error::InsufficientData(data.len(), 0x16));
     }
@@ -39,25 +43,39 @@ pub fn decompress(data: impl AsRef<[u8]>) -> Result<Vec<u8>> {
             "Yaz0",
         ));
     }
+    Ok(header)
+}
+
+/// Decompress Yaz0 data to a new buffer.
+pub fn decompress(data: impl AsRef<[u8]>) -> Result<Bytes> {
+    let data = data.as_ref();
+    let header = read_checked_header(data)?;
+    let mut out = vec![0; header.uncompressed_size as usize];
+    decompress_data(data, &mut out)?;
+    Ok(out.into())
+}
+
+/// Decompress Yaz0 data to a new buffer, failing before anything is allocated
+/// if the header claims a decompressed size larger than `max_size`. Use this
+/// instead of [`decompress`] for untrusted data.
+pub fn decompress_bounded(data: &[u8], max_size: usize) -> Result<Bytes> {
+    let header = read_checked_header(data)?;
+    if header.uncompressed_size as usize > max_size {
+        return Err(Error::InvalidDataD(format!(
+            "Yaz0 decompressed size ({:#x}) exceeds maximum ({:#x})",
+            header.uncompressed_size, max_size
+        )));
+    }
     let mut out = vec![0; header.uncompressed_size as usize];
-    ffi::DecompressIntoBuffer(data, &mut out)?;
-    Ok(out)
+    decompress_data(data, &mut out)?;
+    Ok(out.into())
 }
 
 /// Decompress Yaz0 data into an existing buffer, returning the number of
 /// bytes written.
 pub fn decompress_into(data: impl AsRef<[u8]>, mut buffer: impl AsMut<[u8]>) -> Result<usize> {
     let data = data.as_ref();
-    if data.len() < 0x16 {
-        return Err(Error::InsufficientData(data.len(), 0x16));
-    }
-    let header = get_header(data).ok_or(Error::InvalidData("Missing or corrupt Yaz0 header"))?;
-    if &header.magic != b"Yaz0" {
-        return Err(Error::BadMagic(
-            String::from_utf8_lossy(header.magic.as_slice()).to_string(),
-            "Yaz0",
-        ));
-    }
+    let header = read_checked_header(data)?;
     let buffer = buffer.as_mut();
     if buffer.len() < header.uncompressed_size as usize {
         return Err(Error::InsufficientData(
@@ -65,10 +83,31 @@ pub fn decompress_into(data: impl AsRef<[u8]>, mut buffer: impl AsMut<[u8]>) ->
             header.uncompressed_size as usize,
         ));
     }
-    ffi::DecompressIntoBuffer(data, buffer)?;
+    decompress_data(data, buffer)?;
     Ok(header.uncompressed_size as usize)
 }
 
+/// How much decompressed data [`decompress_to`] buffers before writing it out.
+const STREAM_CHUNK_SIZE: usize = 0x20000;
+
+/// Decompress Yaz0 data into a writer, returning the number of bytes written.
+/// Unlike [`decompress`], this does not allocate a buffer for the whole
+/// output: the data is written in chunks as it is decompressed, keeping only
+/// as much as back-references can reach. This always uses the pure-Rust
+/// decoder, even without the `yaz0-rust` feature.
+pub fn decompress_to<W: Write>(data: &[u8], out: &mut W) -> Result<usize> {
+    let header = read_checked_header(data)?;
+    let size = header.uncompressed_size as usize;
+    let mut window = WindowOutput {
+        out,
+        window: Vec::with_capacity(STREAM_CHUNK_SIZE + MAX_DISTANCE + MAX_MATCH),
+        written: 0,
+    };
+    decode(data, size, &mut window)?;
+    window.out.write_all(&window.window)?;
+    Ok(size)
+}
+
 /// Decompress Yaz0 data into an existing buffer, returning the number of
 /// bytes written.
 ///
@@ -79,6 +118,9 @@ pub fn decompress_into(data: impl AsRef<[u8]>, mut buffer: impl AsMut<[u8]>) ->
 /// data. **Do not use this function on untrusted data.**
 pub unsafe fn decompress_unchecked(data: impl AsRef<[u8]>, mut buffer: impl AsMut<[u8]>) -> usize {
     let data = data.as_ref();
+    #[cfg(feature = "yaz0-rust")]
+    decompress_data(data, buffer.as_mut()).unwrap_unchecked();
+    #[cfg(not(feature = "yaz0-rust"))]
     ffi::DecompressUnsafe(data, buffer.as_mut()).unwrap_unchecked();
     u32::from_be_bytes(data.get_unchecked(0x4..0x8).try_into().unwrap_unchecked()) as usize
 }
@@ -96,7 +138,7 @@ pub fn decompress_if(data: &[u8]) -> Cow<'_, [u8]> {
             return Cow::Borrowed(data);
         }
         let mut out = vec![0; header.uncompressed_size as usize];
-        if ffi::DecompressIntoBuffer(data, &mut out).is_ok() {
+        if decompress_data(data, &mut out).is_ok() {
             Cow::Owned(out)
         } else {
             Cow::Borrowed(data)
@@ -106,11 +148,192 @@ pub fn decompress_if(data: &[u8]) -> Cow<'_, [u8]> {
     }
 }
 
+#[cfg(test)]
+thread_local! {
+    /// Number of times Yaz0 data has been decompressed on this thread, so
+    /// tests can check that nothing is decompressed more than once.
+    pub(crate) static DECOMPRESS_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(not(feature = "yaz0-rust"))]
+#[inline]
+fn decompress_data(data: &[u8], dest: &mut [u8]) -> Result<()> {
+    #[cfg(test)]
+    DECOMPRESS_CALLS.with(|calls| calls.set(calls.get() + 1));
+    ffi::DecompressIntoBuffer(data, dest)?;
+    Ok(())
+}
+
+/// Decompress Yaz0 data (including the header) into `dest`, stopping once the
+/// decompressed size given in the header has been written.
+#[cfg(feature = "yaz0-rust")]
+fn decompress_data(data: &[u8], dest: &mut [u8]) -> Result<()> {
+    #[cfg(test)]
+    DECOMPRESS_CALLS.with(|calls| calls.set(calls.get() + 1));
+    let size = data
+        .get(0x4..0x8)
+        .map(|size| u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        .ok_or(TRUNCATED)?;
+    let dest_len = dest.len();
+    let dest = dest
+        .get_mut(..size)
+        .ok_or(Error::InsufficientData(dest_len, size))?;
+    decode(data, size, &mut SliceOutput { dest, pos: 0 })
+}
+
+const TRUNCATED: Error = Error::InvalidData("Yaz0 data is truncated");
+const BAD_BACK_REFERENCE: Error =
+    Error::InvalidData("Yaz0 back-reference points before the start of the data");
+
+/// Somewhere the Yaz0 decoder can write decompressed data to.
+trait DecodeOutput {
+    /// The number of bytes decompressed so far.
+    fn pos(&self) -> usize;
+    /// Append a literal byte.
+    fn push(&mut self, byte: u8) -> Result<()>;
+    /// Append `length` bytes copied from `distance` bytes back. The ranges may
+    /// overlap, in which case bytes written by this copy are repeated.
+    fn copy_back(&mut self, distance: usize, length: usize) -> Result<()>;
+}
+
+/// Decompresses into a buffer holding the whole output.
+#[cfg(feature = "yaz0-rust")]
+struct SliceOutput<'a> {
+    dest: &'a mut [u8],
+    pos:  usize,
+}
+
+#[cfg(feature = "yaz0-rust")]
+impl DecodeOutput for SliceOutput<'_> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn push(&mut self, byte: u8) -> Result<()> {
+        self.dest[self.pos] = byte;
+        self.pos += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn copy_back(&mut self, distance: usize, length: usize) -> Result<()> {
+        let start = self.pos.checked_sub(distance).ok_or(BAD_BACK_REFERENCE)?;
+        for offset in 0..length {
+            self.dest[self.pos + offset] = self.dest[start + offset];
+        }
+        self.pos += length;
+        Ok(())
+    }
+}
+
+/// Decompresses into a sliding window, writing out everything that
+/// back-references can no longer reach.
+struct WindowOutput<'a, W: Write> {
+    out:     &'a mut W,
+    window:  Vec<u8>,
+    written: usize,
+}
+
+impl<W: Write> WindowOutput<'_, W> {
+    fn flush_chunk(&mut self) -> Result<()> {
+        if self.window.len() >= STREAM_CHUNK_SIZE + MAX_DISTANCE {
+            let end = self.window.len() - MAX_DISTANCE;
+            self.out.write_all(&self.window[..end])?;
+            self.window.drain(..end);
+            self.written += end;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> DecodeOutput for WindowOutput<'_, W> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.written + self.window.len()
+    }
+
+    #[inline]
+    fn push(&mut self, byte: u8) -> Result<()> {
+        self.flush_chunk()?;
+        self.window.push(byte);
+        Ok(())
+    }
+
+    fn copy_back(&mut self, distance: usize, length: usize) -> Result<()> {
+        self.flush_chunk()?;
+        let start = self
+            .window
+            .len()
+            .checked_sub(distance)
+            .ok_or(BAD_BACK_REFERENCE)?;
+        for offset in start..start + length {
+            self.window.push(self.window[offset]);
+        }
+        Ok(())
+    }
+}
+
+/// Decode the compressed stream following the header of Yaz0 data until
+/// `size` bytes have been written to `out`.
+fn decode(data: &[u8], size: usize, out: &mut impl DecodeOutput) -> Result<()> {
+    let mut src_pos = 0x10;
+    let mut group_header = 0u8;
+    let mut remaining_chunks = 0;
+    while out.pos() < size {
+        if remaining_chunks == 0 {
+            group_header = *data.get(src_pos).ok_or(TRUNCATED)?;
+            src_pos += 1;
+            remaining_chunks = 8;
+        }
+        if group_header & 0x80 != 0 {
+            out.push(*data.get(src_pos).ok_or(TRUNCATED)?)?;
+            src_pos += 1;
+        } else {
+            let pair = data.get(src_pos..src_pos + 2).ok_or(TRUNCATED)?;
+            src_pos += 2;
+            let distance = ((((pair[0] & 0xF) as usize) << 8) | pair[1] as usize) + 1;
+            let length = match pair[0] >> 4 {
+                0 => {
+                    let length = *data.get(src_pos).ok_or(TRUNCATED)? as usize + 0x12;
+                    src_pos += 1;
+                    length
+                }
+                n => n as usize + 2,
+            };
+            out.copy_back(distance, length.min(size - out.pos()))?;
+        }
+        group_header <<= 1;
+        remaining_chunks -= 1;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "yaz0-rust"))]
+#[inline]
+fn compress_to_vec(data: &[u8], alignment: u32, level: u8) -> Vec<u8> {
+    ffi::Compress(data, alignment, level as i32)
+}
+
+#[cfg(feature = "yaz0-rust")]
+#[inline]
+fn compress_to_vec(data: &[u8], alignment: u32, level: u8) -> Vec<u8> {
+    compress_data(data, alignment, level.clamp(6, 9), MAX_DISTANCE)
+}
+
 /// Compress data with default compression settings (no alignment, compression
 /// level 7).
-pub fn compress(data: impl AsRef<[u8]>) -> Vec<u8> {
-    let data = data.as_ref();
-    ffi::Compress(data, 0, 7)
+pub fn compress(data: impl AsRef<[u8]>) -> Bytes {
+    compress_to_vec(data.as_ref(), 0, 7).into()
+}
+
+/// Compress data with no alignment at the given compression level (6 to 9; 6
+/// is fastest and 9 is slowest).
+///
+/// Automatically clamps the compression level to 6 to 9.
+pub fn compress_with_level(data: impl AsRef<[u8]>, level: u8) -> Bytes {
+    compress_to_vec(data.as_ref(), 0, level).into()
 }
 
 /// Yaz0 compression options.
@@ -131,16 +354,65 @@ impl Default for CompressOptions {
     }
 }
 
+/// Check that a compression level is in the supported range (6 to 9), for
+/// functions which take one and should not silently clamp it.
+pub(crate) fn validate_level(level: u8) -> Result<()> {
+    if !(6..=9).contains(&level) {
+        return Err(Error::InvalidDataD(format!(
+            "Invalid yaz0 compression level {level} (expected 6 to 9)"
+        )));
+    }
+    Ok(())
+}
+
 /// Compress data with custom compression settings.
 ///
 /// Automatically clamps the compression level to 6 to 9.
-pub fn compress_with_options(data: impl AsRef<[u8]>, options: CompressOptions) -> Vec<u8> {
-    let data = data.as_ref();
-    ffi::Compress(
-        data,
+pub fn compress_with_options(data: impl AsRef<[u8]>, options: CompressOptions) -> Bytes {
+    compress_to_vec(
+        data.as_ref(),
         options.alignment as u32,
-        options.compression_level as i32,
+        options.compression_level,
     )
+    .into()
+}
+
+/// Yaz0 compression parameters for [`compress_with_params`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Yaz0Params {
+    /// Compression level (6 to 9; 6 is fastest and 9 is slowest), which
+    /// controls how many earlier occurrences are checked for each match
+    pub level: u8,
+    /// How far back to search for matches, from 1 to 0x1000 bytes (the
+    /// largest distance Yaz0 can encode)
+    pub search_range: usize,
+}
+
+impl Default for Yaz0Params {
+    fn default() -> Self {
+        Self {
+            level: 7,
+            search_range: MAX_DISTANCE,
+        }
+    }
+}
+
+/// Compress data with a custom match search range as well as compression
+/// level, trading compression ratio for speed. Unlike the other compression
+/// functions, this always uses the pure-Rust encoder (even without the
+/// `yaz0-rust` feature), since syaz0 does not support configuring the search
+/// range.
+///
+/// Returns an error if the level or search range is out of range.
+pub fn compress_with_params(data: impl AsRef<[u8]>, params: Yaz0Params) -> Result<Bytes> {
+    validate_level(params.level)?;
+    if !(1..=MAX_DISTANCE).contains(&params.search_range) {
+        return Err(Error::InvalidDataD(format!(
+            "Invalid yaz0 search range {:#x} (expected 1 to {MAX_DISTANCE:#x})",
+            params.search_range
+        )));
+    }
+    Ok(compress_data(data.as_ref(), 0, params.level, params.search_range).into())
 }
 
 /// Compress data conditionally, if an associated path has a Yaz0-associated
@@ -156,12 +428,154 @@ pub fn compress_if(data: &[u8], path: impl AsRef<std::path::Path>) -> Cow<'_, [u
         .map(|e| e.starts_with('s') && e != "sarc")
         .unwrap_or(false)
     {
-        Cow::Owned(compress(data))
+        compress(data).into()
     } else {
         Cow::Borrowed(data)
     }
 }
 
+/// Read a file and write it to a new path, compressing it if the destination
+/// path has a Yaz0-associated file extension (as with [`compress_if`]).
+/// Returns whether the data was compressed.
+pub fn compress_file(
+    src: impl AsRef<std::path::Path>,
+    dst: impl AsRef<std::path::Path>,
+) -> Result<bool> {
+    let data = std::fs::read(src.as_ref())?;
+    let output = compress_if(&data, dst.as_ref());
+    std::fs::write(dst.as_ref(), &output)?;
+    Ok(matches!(output, Cow::Owned(_)))
+}
+
+/// Largest distance which a Yaz0 back-reference can encode.
+const MAX_DISTANCE: usize = 0x1000;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 0x111;
+const HASH_BITS: u32 = 15;
+const NO_POS: u32 = u32::MAX;
+
+/// Hash chains over the 3-byte prefixes of every position inserted so far,
+/// used to find earlier occurrences of the data at a position.
+struct MatchFinder<'a> {
+    data: &'a [u8],
+    head: Vec<u32>,
+    prev: Vec<u32>,
+}
+
+impl<'a> MatchFinder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            head: vec![NO_POS; 1 << HASH_BITS],
+            prev: vec![NO_POS; data.len()],
+        }
+    }
+
+    #[inline]
+    fn hash(&self, pos: usize) -> usize {
+        let value = u32::from_be_bytes([0, self.data[pos], self.data[pos + 1], self.data[pos + 2]]);
+        (value.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    }
+
+    #[inline]
+    fn insert(&mut self, pos: usize) {
+        if pos + MIN_MATCH <= self.data.len() {
+            let hash = self.hash(pos);
+            self.prev[pos] = self.head[hash];
+            self.head[hash] = pos as u32;
+        }
+    }
+
+    /// Find the longest match for the data at `pos`, returning its length and
+    /// distance. At most `max_chain` candidates are checked, and none further
+    /// back than `search_range`.
+    fn find(&self, pos: usize, search_range: usize, max_chain: usize) -> (usize, usize) {
+        let max_len = MAX_MATCH.min(self.data.len() - pos);
+        let mut best = (0, 0);
+        if max_len < MIN_MATCH {
+            return best;
+        }
+        let target = &self.data[pos..pos + max_len];
+        let mut candidate = self.head[self.hash(pos)];
+        for _ in 0..max_chain {
+            if candidate == NO_POS || pos - candidate as usize > search_range {
+                break;
+            }
+            let start = candidate as usize;
+            if self.data[start + best.0] == target[best.0] {
+                let len = self.data[start..]
+                    .iter()
+                    .zip(target)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best.0 {
+                    best = (len, pos - start);
+                    if len == max_len {
+                        break;
+                    }
+                }
+            }
+            candidate = self.prev[start];
+        }
+        best
+    }
+}
+
+/// Compress data with the pure-Rust encoder, choosing the longest match found
+/// at each position. Higher levels check more earlier occurrences for each
+/// match, which is slower but may find longer matches.
+fn compress_data(data: &[u8], alignment: u32, level: u8, search_range: usize) -> Vec<u8> {
+    let max_chain = match level {
+        ..=6 => 128,
+        7 => 256,
+        8 => 1024,
+        _ => 4096,
+    };
+    let search_range = search_range.min(MAX_DISTANCE);
+    let mut out = Vec::with_capacity(0x10 + data.len() + data.len().div_ceil(8));
+    out.extend_from_slice(b"Yaz0");
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(&alignment.to_be_bytes());
+    out.extend_from_slice(&[0; 4]);
+
+    let mut finder = MatchFinder::new(data);
+    let mut group_offset = 0;
+    let mut group_len = 8;
+    let mut pos = 0;
+    while pos < data.len() {
+        if group_len == 8 {
+            group_offset = out.len();
+            out.push(0);
+            group_len = 0;
+        }
+        let (len, distance) = finder.find(pos, search_range, max_chain);
+        if len >= MIN_MATCH {
+            let distance = distance - 1;
+            if len < 0x12 {
+                out.push(((len - 2) << 4 | distance >> 8) as u8);
+                out.push(distance as u8);
+            } else {
+                out.push((distance >> 8) as u8);
+                out.push(distance as u8);
+                out.push((len - 0x12) as u8);
+            }
+            for i in pos..pos + len {
+                finder.insert(i);
+            }
+            pos += len;
+        } else {
+            out[group_offset] |= 0x80 >> group_len;
+            out.push(data[pos]);
+            finder.insert(pos);
+            pos += 1;
+        }
+        group_len += 1;
+    }
+    out
+}
+
+#[cfg(feature = "yaz0")]
+#[cfg_attr(feature = "yaz0-rust", allow(dead_code))]
 #[cxx::bridge(namespace = "oead::yaz0")]
 mod ffi {
     unsafe extern "C++" {
@@ -220,6 +634,117 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_roundtrip_sarc() {
+        for file in std::fs::read_dir("test/sarc").unwrap() {
+            let data = std::fs::read(file.unwrap().path()).unwrap();
+            for level in [6, 9] {
+                let compressed = super::compress_with_level(&data, level);
+                assert_eq!(super::decompress(compressed).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompress_to() {
+        for (file, _, len) in FILES {
+            let path = std::path::Path::new("test/yaz0").join(file);
+            let data = std::fs::read(path).unwrap();
+            let mut out = Vec::new();
+            assert_eq!(super::decompress_to(&data, &mut out).unwrap(), *len);
+            assert_eq!(out.as_slice(), super::decompress(&data).unwrap().as_ref());
+        }
+        let path = std::path::Path::new("test/yaz0").join(FILES[3].0);
+        let data = std::fs::read(path).unwrap();
+        assert!(super::decompress_to(&data[..data.len() / 2], &mut std::io::sink()).is_err());
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut data = b"Yaz0".to_vec();
+        data.extend_from_slice(&0x4000_0000u32.to_be_bytes());
+        data.resize(0x20, 0);
+        match super::decompress_bounded(&data, 0x100000) {
+            Err(crate::Error::InvalidDataD(msg)) => assert!(msg.contains("exceeds maximum")),
+            other => panic!("Expected size error, got {:?}", other),
+        }
+
+        let path = std::path::Path::new("test/yaz0").join(FILES[3].0);
+        let data = std::fs::read(path).unwrap();
+        let decompressed = super::decompress_bounded(&data, FILES[3].2).unwrap();
+        assert_eq!(decompressed.len(), FILES[3].2);
+        assert!(super::decompress_bounded(&data, FILES[3].2 - 1).is_err());
+    }
+
+    #[test]
+    fn test_compress_file() {
+        let src = std::path::Path::new("test/sarc/test.sarc");
+        let data = std::fs::read(src).unwrap();
+        let dir = std::env::temp_dir();
+
+        let dst = dir.join("roead_compress_file.ssarc");
+        assert!(super::compress_file(src, &dst).unwrap());
+        let output = std::fs::read(&dst).unwrap();
+        assert_eq!(&output[..4], b"Yaz0");
+        assert_eq!(super::decompress(&output).unwrap(), data);
+        std::fs::remove_file(dst).unwrap();
+
+        let dst = dir.join("roead_compress_file.sarc");
+        assert!(!super::compress_file(src, &dst).unwrap());
+        assert_eq!(std::fs::read(&dst).unwrap(), data);
+        std::fs::remove_file(dst).unwrap();
+    }
+
+    #[test]
+    fn test_compress_with_params() {
+        let data = std::fs::read("test/byml/A-1_Dynamic.byml").unwrap();
+        let narrow = super::compress_with_params(&data, super::Yaz0Params {
+            level: 7,
+            search_range: 0x100,
+        })
+        .unwrap();
+        let wide = super::compress_with_params(&data, super::Yaz0Params::default()).unwrap();
+        assert!(wide.len() <= narrow.len());
+        assert_eq!(super::decompress(&narrow).unwrap(), data);
+        assert_eq!(super::decompress(&wide).unwrap(), data);
+        assert!(
+            super::compress_with_params(&data, super::Yaz0Params {
+                level: 5,
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            super::compress_with_params(&data, super::Yaz0Params {
+                search_range: 0x1001,
+                ..Default::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[cfg(all(feature = "yaz0", feature = "yaz0-rust"))]
+    #[test]
+    fn test_rust_decompress() {
+        for (file, ..) in FILES {
+            let path = std::path::Path::new("test/yaz0").join(file);
+            let data = std::fs::read(path).unwrap();
+            let header = super::get_header(&data).unwrap();
+            let mut expected = vec![0; header.uncompressed_size as usize];
+            super::ffi::DecompressIntoBuffer(&data, &mut expected).unwrap();
+            assert_eq!(super::decompress(&data).unwrap(), expected);
+        }
+    }
+
+    #[cfg(feature = "yaz0-rust")]
+    #[test]
+    fn test_truncated() {
+        let path = std::path::Path::new("test/yaz0").join(FILES[3].0);
+        let data = std::fs::read(path).unwrap();
+        assert!(super::decompress(&data[..data.len() / 2]).is_err());
+        assert!(super::decompress_if(&data[..data.len() / 2]).starts_with(b"Yaz0"));
+    }
+
     #[test]
     fn test_unchecked() {
         let data = b"Nothing you have not given away will ever really be yours.";